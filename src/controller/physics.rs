@@ -23,24 +23,21 @@ impl Physics {
     pub fn fire(args: Option<PhysicsArgs>) -> Physics {
         match args {
             Some(args) => Physics{
-                _angle: match args._angle {
-                    Some(angle) => angle,
-                    None => 0f32,
-                },
-                _drag: match args._drag {
-                    Some(drag) => drag,
-                    None => 0f32,
-                },
-                _initial_power: match args._initial_power {
-                    Some(power) => power,
-                    None => 0f32,
-                }
+                _angle: args._angle.unwrap_or(0f32),
+                _drag: args._drag.unwrap_or(0f32),
+                _initial_power: args._initial_power.unwrap_or(0f32),
             },
             None => Physics::empty(),
         }
     }
 }
 
+impl Default for PhysicsArgs {
+    fn default() -> PhysicsArgs {
+        PhysicsArgs::new()
+    }
+}
+
 impl PhysicsArgs {
     pub fn new() -> PhysicsArgs {
         PhysicsArgs{
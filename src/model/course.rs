@@ -0,0 +1,158 @@
+// The largest handicap, plus or minus, that strokes are given for
+pub const MAX_HANDICAP: f32 = 54.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hole {
+    _name: String,
+    _par: i32,
+    _stroke_index: usize,
+}
+
+impl Hole {
+    // stroke_index ranks the hole by difficulty, 1 being the hardest on the course
+    pub fn new(name: String, par: i32, stroke_index: usize) -> Hole {
+        Hole {
+            _name: name,
+            _par: par,
+            _stroke_index: stroke_index,
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self._name
+    }
+
+    pub fn par(&self) -> i32 {
+        self._par
+    }
+
+    pub fn stroke_index(&self) -> usize {
+        self._stroke_index
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Course {
+    _name: String,
+    _holes: Vec<Hole>,
+}
+
+impl Course {
+    // Stroke indices must rank the holes 1 to n, each index used once
+    pub fn new(name: String, holes: Vec<Hole>) -> Result<Course, String> {
+        let mut indices: Vec<usize> = holes.iter().map(|hole| hole.stroke_index()).collect();
+        indices.sort();
+
+        if let Some((expected, &found)) = indices.iter().enumerate().map(|(idx, found)| (idx + 1, found)).find(|&(expected, &found)| expected != found) {
+            return Err(format!("Course {} has stroke index {} where {} was expected", name, found, expected));
+        }
+
+        Ok(Course {
+            _name: name,
+            _holes: holes,
+        })
+    }
+
+    pub fn name(&self) -> &String {
+        &self._name
+    }
+
+    pub fn holes(&self) -> &[Hole] {
+        &self._holes
+    }
+
+    pub fn par(&self) -> i32 {
+        self._holes.iter().map(|hole| hole.par()).sum()
+    }
+
+    // Handicap strokes given on each hole. Strokes are spread evenly over the
+    // course, with any remainder going to the hardest holes first. A plus
+    // (negative) handicap gives strokes back on the easiest holes instead.
+    // Handicaps beyond MAX_HANDICAP count as MAX_HANDICAP, and one that isn't
+    // a number as scratch.
+    pub fn strokes_received(&self, handicap: f32) -> Vec<i32> {
+        let hole_count = self._holes.len();
+
+        if hole_count == 0 {
+            return Vec::new();
+        }
+
+        let playing_handicap = if handicap.is_nan() { 0 } else { handicap.clamp(-MAX_HANDICAP, MAX_HANDICAP).round() as i32 };
+        let per_hole = playing_handicap.abs() / hole_count as i32;
+        let remainder = (playing_handicap.abs() % hole_count as i32) as usize;
+        let sign = playing_handicap.signum();
+
+        self._holes
+            .iter()
+            .map(|hole| {
+                let extra = if sign >= 0 {
+                    hole.stroke_index() <= remainder
+                } else {
+                    hole.stroke_index() > hole_count - remainder
+                };
+
+                sign * (per_hole + extra as i32)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Course, Hole};
+
+    fn test_course() -> Course {
+        Course::new(
+            "Test".to_string(),
+            vec![
+                Hole::new("Tutorial".to_string(), 3, 3),
+                Hole::new("Danger".to_string(), 4, 1),
+                Hole::new("Spiral".to_string(), 5, 2),
+            ]
+        ).unwrap()
+    }
+
+    #[test]
+    fn total_par() {
+        assert_eq!(test_course().par(), 12);
+    }
+
+    #[test]
+    fn strokes_go_to_hardest_holes() {
+        let test = test_course();
+
+        assert_eq!(test.strokes_received(0f32), vec![0, 0, 0]);
+        assert_eq!(test.strokes_received(2f32), vec![0, 1, 1]);
+        assert_eq!(test.strokes_received(4.4), vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn plus_handicap_gives_back_on_easiest_holes() {
+        assert_eq!(test_course().strokes_received(-1f32), vec![-1, 0, 0]);
+    }
+
+    #[test]
+    fn wild_handicaps_are_clamped() {
+        let test = test_course();
+
+        assert_eq!(test.strokes_received(f32::MAX), test.strokes_received(54f32));
+        assert_eq!(test.strokes_received(f32::NEG_INFINITY), vec![-18, -18, -18]);
+        assert_eq!(test.strokes_received(f32::NAN), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn stroke_indices_must_rank_every_hole_once() {
+        let hole = |index| Hole::new("Hole".to_string(), 3, index);
+
+        assert_eq!(
+            Course::new("Zero".to_string(), vec![hole(0), hole(1)]),
+            Err("Course Zero has stroke index 0 where 1 was expected".to_string())
+        );
+        assert_eq!(
+            Course::new("Twice".to_string(), vec![hole(1), hole(1)]),
+            Err("Course Twice has stroke index 1 where 2 was expected".to_string())
+        );
+        assert!(Course::new("Gap".to_string(), vec![hole(1), hole(3)]).is_err());
+        assert!(Course::new("Empty".to_string(), Vec::new()).is_ok());
+    }
+}
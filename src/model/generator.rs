@@ -0,0 +1,298 @@
+use model::map::{Map, MapBuilder, MapObject, Terrain};
+
+pub const MIN_DIFFICULTY: u32 = 1;
+pub const MAX_DIFFICULTY: u32 = 5;
+
+// Rough left around the course on every side, as in the bundled maps
+const MARGIN: usize = 2;
+
+// SplitMix64; small, fast and the same on every platform, so a seed always
+// gives the same course
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeededRng {
+    _state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> SeededRng {
+        SeededRng { _state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self._state = self._state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self._state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // A number in low..=high
+    pub fn range(&mut self, low: usize, high: usize) -> usize {
+        low + (self.next_u64() % (high - low + 1) as u64) as usize
+    }
+
+    pub fn coin_flip(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+}
+
+// Builds practice holes: a walled corridor running down from a tee to a hole,
+// bending left and right along diagonal walls. Higher difficulties make the
+// corridor narrower and longer, with more bends and obstacles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CourseGenerator {
+    _seed: u64,
+    _difficulty: u32,
+    _obstacles: bool,
+}
+
+impl CourseGenerator {
+    pub fn new(seed: u64, difficulty: u32) -> Result<CourseGenerator, String> {
+        if !(MIN_DIFFICULTY..=MAX_DIFFICULTY).contains(&difficulty) {
+            return Err(format!("Difficulty {} is not between {} and {}", difficulty, MIN_DIFFICULTY, MAX_DIFFICULTY));
+        }
+
+        Ok(CourseGenerator {
+            _seed: seed,
+            _difficulty: difficulty,
+            _obstacles: false,
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self._seed
+    }
+
+    pub fn difficulty(&self) -> u32 {
+        self._difficulty
+    }
+
+    pub fn obstacles(&self) -> bool {
+        self._obstacles
+    }
+
+    // Scatters windmills and bumpers along the fairway
+    pub fn set_obstacles(&mut self, obstacles: bool) {
+        self._obstacles = obstacles;
+    }
+
+    pub fn generate(&self) -> Result<Map, String> {
+        let mut rng = SeededRng::new(self._seed);
+        let difficulty = self._difficulty as usize;
+        let fairway_width = 6usize.saturating_sub(difficulty).max(2);
+        let shifts = corridor_shifts(&mut rng, difficulty);
+
+        // Left edge of the fairway on each row, relative to the first row
+        let mut offsets = Vec::with_capacity(shifts.len());
+        let mut offset = 0isize;
+
+        for shift in &shifts {
+            offset += shift;
+            offsets.push(offset);
+        }
+
+        let lowest = *offsets.iter().min().unwrap();
+        let highest = *offsets.iter().max().unwrap();
+        let lefts: Vec<usize> = offsets.iter().map(|offset| (offset - lowest) as usize + MARGIN + 1).collect();
+        let width = (highest - lowest) as usize + fairway_width + 2 * (MARGIN + 1);
+
+        let mut rows = vec![vec![Terrain::Rough; width]; MARGIN];
+        rows.push(cap_row(width, lefts[0], fairway_width));
+
+        for (&left, &shift) in lefts.iter().zip(&shifts) {
+            let wall = match shift {
+                1 => Terrain::WallBackslash,
+                -1 => Terrain::WallSlash,
+                _ => Terrain::WallVertical,
+            };
+
+            let mut row = vec![Terrain::Rough; width];
+            row[left - 1] = wall;
+            row[left + fairway_width] = wall;
+
+            for cell in &mut row[left..left + fairway_width] {
+                *cell = Terrain::Fairway;
+            }
+
+            rows.push(row);
+        }
+
+        rows.push(cap_row(width, *lefts.last().unwrap(), fairway_width));
+        rows.extend(vec![vec![Terrain::Rough; width]; MARGIN]);
+
+        let first = MARGIN + 1;
+        let last = first + lefts.len() - 1;
+
+        rows[first][rng.range(lefts[0], lefts[0] + fairway_width - 1)] = Terrain::Tee;
+        rows[last][rng.range(lefts[lefts.len() - 1], lefts[lefts.len() - 1] + fairway_width - 1)] = Terrain::Hole;
+
+        let text = rows
+            .iter()
+            .map(|row| row.iter().map(|terrain| terrain.glyph()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut map = MapBuilder::from_text(&format!("Generated {}", self._seed), &text)?;
+
+        map.metadata_mut().set_par(Some(2 + (self._difficulty as i32 + 1) / 2));
+        map.metadata_mut().set_difficulty(Some(self._difficulty));
+
+        if self._obstacles {
+            // Keep the tee and hole rows clear so the first and last shots are fair
+            for _ in 0..difficulty {
+                let y = rng.range(first + 1, last - 1);
+                let x = rng.range(lefts[y - first], lefts[y - first] + fairway_width - 1);
+                let object = if rng.coin_flip() { MapObject::Windmill } else { MapObject::Bumper };
+
+                map.set_object(x, y, Some(object))?;
+            }
+        }
+
+        map.validate()?;
+
+        Ok(map)
+    }
+}
+
+// How far each fairway row moves sideways from the one above: straight runs
+// separated by one diagonal bend per difficulty level
+fn corridor_shifts(rng: &mut SeededRng, difficulty: usize) -> Vec<isize> {
+    let mut shifts = vec![0, 0];
+
+    for _ in 0..difficulty {
+        let direction = if rng.coin_flip() { 1 } else { -1 };
+
+        let bend = rng.range(2, 3);
+        let straight = rng.range(1, 3);
+
+        shifts.extend(vec![direction; bend]);
+        shifts.extend(vec![0; straight]);
+    }
+
+    shifts.push(0);
+    shifts
+}
+
+// The wall closing off the top or bottom of the corridor
+fn cap_row(width: usize, left: usize, fairway_width: usize) -> Vec<Terrain> {
+    let mut row = vec![Terrain::Rough; width];
+    row[left - 1] = Terrain::WallVertical;
+    row[left + fairway_width] = Terrain::WallVertical;
+
+    for cell in &mut row[left..left + fairway_width] {
+        *cell = Terrain::WallHorizontal;
+    }
+
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CourseGenerator, SeededRng, MAX_DIFFICULTY, MIN_DIFFICULTY};
+    use model::map::{MapBuilder, Terrain};
+
+    #[test]
+    fn same_seed_same_course() {
+        let generator = CourseGenerator::new(42, 3).unwrap();
+
+        assert_eq!(generator.generate().unwrap(), generator.generate().unwrap());
+        assert_ne!(generator.generate().unwrap().to_string(), CourseGenerator::new(43, 3).unwrap().generate().unwrap().to_string());
+    }
+
+    #[test]
+    fn every_seed_and_difficulty_is_valid() {
+        for difficulty in MIN_DIFFICULTY..=MAX_DIFFICULTY {
+            for seed in 0..200 {
+                let mut generator = CourseGenerator::new(seed, difficulty).unwrap();
+                generator.set_obstacles(seed % 2 == 0);
+
+                let map = generator.generate().unwrap();
+
+                assert_eq!(map.validate(), Ok(()));
+                assert_eq!(map.metadata().difficulty(), Some(difficulty));
+            }
+        }
+    }
+
+    #[test]
+    fn output_round_trips() {
+        for difficulty in MIN_DIFFICULTY..=MAX_DIFFICULTY {
+            for seed in 0..50 {
+                let mut generator = CourseGenerator::new(seed, difficulty).unwrap();
+                generator.set_obstacles(seed % 2 == 0);
+
+                let map = generator.generate().unwrap();
+                let grid = MapBuilder::from_text(map.name(), &map.to_string()).unwrap();
+
+                // The bare grid keeps the board; the map file keeps everything
+                assert_eq!(grid.board(), map.board());
+                assert_eq!(grid.to_string(), map.to_string());
+                assert_eq!(MapBuilder::from_text("", &map.to_map_file().unwrap()).unwrap(), map);
+            }
+        }
+    }
+
+    #[test]
+    fn corridor_is_walled() {
+        for seed in 0..50 {
+            let map = CourseGenerator::new(seed, 4).unwrap().generate().unwrap();
+            let board = map.board();
+
+            // Every playable cell is closed off on all four sides by play or walls
+            for y in 0..map.length() {
+                for x in 0..map.width() {
+                    if board[y][x].terrain() == Terrain::Rough || board[y][x].terrain().is_wall() {
+                        continue;
+                    }
+
+                    for &(nx, ny) in &[(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                        assert_ne!(board[ny][nx].terrain(), Terrain::Rough, "seed {} leaks at ({}, {})", seed, x, y);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn harder_courses_bend_more() {
+        let easy = CourseGenerator::new(7, 1).unwrap().generate().unwrap();
+        let hard = CourseGenerator::new(7, 5).unwrap().generate().unwrap();
+        let diagonals = |map: &::model::map::Map| map.to_string().chars().filter(|&ch| ch == '\\' || ch == '/').count();
+
+        assert!(hard.length() > easy.length());
+        assert!(diagonals(&hard) > diagonals(&easy));
+        assert!(hard.metadata().par() > easy.metadata().par());
+    }
+
+    #[test]
+    fn obstacles_are_optional() {
+        let mut generator = CourseGenerator::new(9, 5).unwrap();
+        let count = |map: &::model::map::Map| map.objects().iter().flatten().filter(|object| object.is_some()).count();
+
+        assert_eq!(count(&generator.generate().unwrap()), 0);
+
+        generator.set_obstacles(true);
+
+        assert!(count(&generator.generate().unwrap()) > 0);
+    }
+
+    #[test]
+    fn difficulty_is_bounded() {
+        assert!(CourseGenerator::new(1, 0).is_err());
+        assert!(CourseGenerator::new(1, MAX_DIFFICULTY + 1).is_err());
+    }
+
+    #[test]
+    fn rng_is_deterministic() {
+        let mut first = SeededRng::new(5);
+        let mut second = SeededRng::new(5);
+
+        for _ in 0..100 {
+            let value = first.range(3, 8);
+
+            assert_eq!(value, second.range(3, 8));
+            assert!((3..=8).contains(&value));
+        }
+    }
+}
@@ -1,10 +1,24 @@
+use std::fmt;
+
 // const valid_characters: &'static str = "[H|_,*#]";
-pub const ANGLED: &'static str = include_str!("Maps/Angled.txt");
-pub const DANGER: &'static str = include_str!("Maps/Danger.txt");
-pub const REVERSE: &'static str = include_str!("Maps/Reverse.txt");
-pub const SPIRAL: &'static str = include_str!("Maps/Spiral.txt");
-pub const TUTORIAL: &'static str = include_str!("Maps/Tutorial.txt");
-pub const WIGGLER: &'static str = include_str!("Maps/Wiggler.txt");
+pub const ANGLED: &str = include_str!("Maps/Angled.txt");
+pub const DANGER: &str = include_str!("Maps/Danger.txt");
+pub const REVERSE: &str = include_str!("Maps/Reverse.txt");
+pub const SPIRAL: &str = include_str!("Maps/Spiral.txt");
+pub const TUTORIAL: &str = include_str!("Maps/Tutorial.txt");
+pub const WIGGLER: &str = include_str!("Maps/Wiggler.txt");
+
+// Map files may start with this line followed by the format version, then a
+// "key: value" header closed by HEADER_END. Files without it are bare glyphs.
+pub const MAP_FILE_MAGIC: &str = "GolfMania map v";
+pub const MAP_FILE_VERSION: u32 = 2;
+const HEADER_END: &str = "---";
+
+// From version 2 the terrain grid may be followed by further layers, each
+// starting with a "--- <layer>" line and aligned cell for cell with the terrain
+const ELEVATION_SECTION: &str = "--- elevation";
+const OBJECTS_SECTION: &str = "--- objects";
+const NO_OBJECT: char = '.';
 
 #[derive(Debug)]
 pub enum MapName {
@@ -16,12 +30,106 @@ pub enum MapName {
     Wiggler,
 }
 
+impl MapName {
+    // Every map bundled with the library
+    pub fn all() -> Vec<MapName> {
+        vec![MapName::Angled, MapName::Danger, MapName::Reverse, MapName::Spiral, MapName::Tutorial, MapName::Wiggler]
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Terrain {
+    Rough,
+    Fairway,
+    Tee,
+    Hole,
+    WallVertical,
+    WallHorizontal,
+    WallBackslash,
+    WallSlash,
+}
+
+impl Terrain {
+    // The character used for this terrain in the map files
+    pub fn glyph(&self) -> char {
+        match *self {
+            Terrain::Rough => 'H',
+            Terrain::Fairway => ',',
+            Terrain::Tee => '*',
+            Terrain::Hole => '#',
+            Terrain::WallVertical => '|',
+            Terrain::WallHorizontal => '_',
+            Terrain::WallBackslash => '\\',
+            Terrain::WallSlash => '/',
+        }
+    }
+
+    pub fn is_wall(&self) -> bool {
+        matches!(*self, Terrain::WallVertical | Terrain::WallHorizontal | Terrain::WallBackslash | Terrain::WallSlash)
+    }
+
+    // The same wall after flipping the map left to right or top to bottom
+    fn mirrored(self) -> Terrain {
+        match self {
+            Terrain::WallBackslash => Terrain::WallSlash,
+            Terrain::WallSlash => Terrain::WallBackslash,
+            terrain => terrain,
+        }
+    }
+
+    // The same wall after swapping the map's rows and columns
+    fn transposed(self) -> Terrain {
+        match self {
+            Terrain::WallVertical => Terrain::WallHorizontal,
+            Terrain::WallHorizontal => Terrain::WallVertical,
+            terrain => terrain,
+        }
+    }
+}
+
 #[derive(Builder, Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Cell {
     foreground: char,
     background: char,
 }
 
+impl Cell {
+    pub fn foreground(&self) -> char {
+        self.foreground
+    }
+
+    pub fn background(&self) -> char {
+        self.background
+    }
+
+    pub fn terrain(&self) -> Terrain {
+        match (self.foreground, self.background) {
+            ('*', _) => Terrain::Tee,
+            (_, '|') => Terrain::WallVertical,
+            (_, '_') => Terrain::WallHorizontal,
+            (_, '\\') => Terrain::WallBackslash,
+            (_, '/') => Terrain::WallSlash,
+            (_, '#') => Terrain::Hole,
+            (_, ',') => Terrain::Fairway,
+            _ => Terrain::Rough,
+        }
+    }
+
+    // The character this cell is written as in the map files
+    pub fn glyph(&self) -> char {
+        self.terrain().glyph()
+    }
+}
+
+impl From<Terrain> for Cell {
+    fn from(terrain: Terrain) -> Cell {
+        match terrain {
+            Terrain::Tee => Cell { foreground: '*', background: ',' },
+            terrain => Cell { foreground: ' ', background: terrain.glyph() },
+        }
+    }
+}
+
 impl CellBuilder {
     pub fn build_string(input: &str) -> Result<Vec<Cell>, String> {
         // Run through the string, building up the Map
@@ -86,302 +194,1769 @@ impl CellBuilder {
                             }
                         ),
                     ex =>
-                        return Err(format!("Invalid Character used {}", ex)),
+                        Err(format!("Invalid Character used {}", ex)),
                 }
             ).collect()
     }
 }
 
-#[derive(Builder, Clone, Debug, Eq, PartialEq)]
-pub struct Map {
-    name: &'static str,
-    board: Vec<Vec<Cell>>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wind {
+    _speed: f32,
+    _angle: f32,
 }
 
-impl Map {
-    pub fn name(&self) -> &'static str {
-        self.name
+impl Wind {
+    // angle is the direction the wind blows towards, in degrees
+    pub fn new(speed: f32, angle: f32) -> Wind {
+        Wind {
+            _speed: speed,
+            _angle: angle,
+        }
     }
 
-    pub fn board(&self) -> Vec<Vec<Cell>> {
-        self.board.to_vec()
+    pub fn speed(&self) -> f32 {
+        self._speed
     }
 
-    pub fn length(&self) -> usize {
-        self.board.len()
+    pub fn angle(&self) -> f32 {
+        self._angle
     }
+}
 
-    pub fn width(&self) -> usize {
-        self.board.first().unwrap().len()
-    }
+// Everything in a map file's header besides the name; all of it is optional
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MapMetadata {
+    _par: Option<i32>,
+    _author: Option<String>,
+    _difficulty: Option<u32>,
+    _wind: Option<Wind>,
 }
 
-impl<'a> IntoIterator for &'a Map {
-    type Item = Cell;
-    type IntoIter = MapIntoIterator<'a>;
+impl MapMetadata {
+    pub fn par(&self) -> Option<i32> {
+        self._par
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        MapIntoIterator { map: self, x: 0, y: 0, direction: Direction::Horizontal }
+    pub fn set_par(&mut self, par: Option<i32>) {
+        self._par = par;
     }
-}
 
-#[derive(Debug, Eq, PartialEq)]
-pub enum Direction {
-    Vertical,
-    Horizontal,
-}
+    pub fn author(&self) -> Option<&String> {
+        self._author.as_ref()
+    }
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct MapIntoIterator<'a> {
-    map: &'a Map,
-    x: usize,
-    y: usize,
-    direction: Direction,
-}
+    pub fn set_author(&mut self, author: Option<String>) {
+        self._author = author;
+    }
 
-impl<'a> MapIntoIterator<'a> {
-    pub fn change_direction(&mut self, new_direction: Direction) {
-        self.direction = new_direction;
+    pub fn difficulty(&self) -> Option<u32> {
+        self._difficulty
     }
 
-    pub fn direction(self) -> Direction {
-        self.direction
+    pub fn set_difficulty(&mut self, difficulty: Option<u32>) {
+        self._difficulty = difficulty;
     }
-}
 
-impl<'a> Iterator for MapIntoIterator<'a> {
-    type Item = Cell;
+    pub fn wind(&self) -> Option<Wind> {
+        self._wind
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.x >= self.map.width() - 1 && self.y >= self.map.length() - 1{
-            return None;
-        }
+    pub fn set_wind(&mut self, wind: Option<Wind>) {
+        self._wind = wind;
+    }
 
-        let result = self.map.board[self.y][self.x];
+    fn parse_field(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "par" => self._par = Some(value.parse().map_err(|_| format!("Invalid par {}", value))?),
+            "author" => self._author = Some(value.to_string()),
+            "difficulty" => self._difficulty = Some(value.parse().map_err(|_| format!("Invalid difficulty {}", value))?),
+            "wind" => {
+                let parts = value
+                    .split_whitespace()
+                    .map(|part| part.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("Invalid wind {}", value))?;
 
-        match self.direction {
-            Direction::Horizontal => {
-                if self.x + 1 >= self.map.width() {
-                    self.x = 0;
-                    self.y += 1;
-                }
-                else {
-                    self.x += 1;
+                match parts[..] {
+                    [speed, angle] => self._wind = Some(Wind::new(speed, angle)),
+                    _ => return Err(format!("Wind needs a speed and an angle, got {}", value)),
                 }
             },
-            Direction::Vertical => {
-                if self.y + 1 >= self.map.length() {
-                    self.y = 0;
-                    self.x += 1;
-                }
-                else {
-                    self.y += 1;
-                }
-            }
+            key => return Err(format!("Unknown map header field {}", key)),
         }
 
-        Some(result)
+        Ok(())
     }
 }
 
-trait MapCompiler {
-     fn compile_string<'a>(&self) -> Vec<Vec<Cell>>;
+// Things placed on top of the terrain that the ball can run into
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum MapObject {
+    Windmill,
+    Bumper,
 }
 
-impl MapCompiler for str {
-     fn compile_string<'a>(&self) -> Vec<Vec<Cell>> {
-        self
-            .lines()
-            .map( |line| CellBuilder::build_string(line).unwrap() )
-            .collect()
-     }
+impl MapObject {
+    // The character used for this object in the objects layer of a map file
+    pub fn glyph(&self) -> char {
+        match *self {
+            MapObject::Windmill => 'W',
+            MapObject::Bumper => 'B',
+        }
+    }
+
+    pub fn from_glyph(glyph: char) -> Result<Option<MapObject>, String> {
+        match glyph {
+            NO_OBJECT => Ok(None),
+            'W' => Ok(Some(MapObject::Windmill)),
+            'B' => Ok(Some(MapObject::Bumper)),
+            ex => Err(format!("Invalid object used {}", ex)),
+        }
+    }
 }
 
-impl MapBuilder
-{
-    pub fn from_named(map_name : Option<MapName>) -> Result<Map, String> {
-        match map_name {
-            Some(map_val) => {
-                MapBuilder::load_map(map_val)
-            },
-            None => MapBuilder::load_map(MapName::Tutorial),
+// Heights run from 0 to 35 so each fits in one base 36 digit in the map file
+pub const MAX_ELEVATION: u8 = 35;
+
+// A cell along with where it is on the map
+pub type PositionedCell<'a> = ((usize, usize), &'a Cell);
+
+// Everything the map knows about one position: where it is, its terrain cell,
+// its elevation and any object standing on it
+pub type LayeredCell<'a> = ((usize, usize), &'a Cell, u8, Option<MapObject>);
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Map {
+    name: String,
+    board: Vec<Vec<Cell>>,
+    metadata: MapMetadata,
+    // Layers aligned with board, one entry per cell
+    elevation: Vec<Vec<u8>>,
+    objects: Vec<Vec<Option<MapObject>>>,
+}
+
+impl Map {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn metadata(&self) -> &MapMetadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut MapMetadata {
+        &mut self.metadata
+    }
+
+    pub fn board(&self) -> Vec<Vec<Cell>> {
+        self.board.to_vec()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&Cell> {
+        self.board.get(y).and_then(|row| row.get(x))
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[Cell]> {
+        self.board.get(y).map(|row| &row[..])
+    }
+
+    // Columns aren't contiguous, so this collects references top to bottom
+    pub fn column(&self, x: usize) -> Option<Vec<&Cell>> {
+        if x < self.width() {
+            Some(self.board.iter().map(|row| &row[x]).collect())
+        } else {
+            None
         }
     }
 
-    fn load_map(selected_map: MapName) -> Result<Map, String>{
-        match selected_map {
-            MapName::Angled => MapBuilder::default()
-                .name("Angled")
-                .board(ANGLED.compile_string())
-                .build()
-            ,
-            MapName::Danger => MapBuilder::default()
-                .name("Danger")
-                .board(DANGER.compile_string())
-                .build()
-            ,
-            MapName::Reverse => MapBuilder::default()
-                .name("Reverse")
-                .board(REVERSE.compile_string())
-                .build()
-            ,
-            MapName::Spiral => MapBuilder::default()
-                .name("Spiral")
-                .board(SPIRAL.compile_string())
-                .build()
-            ,
-            MapName::Wiggler => MapBuilder::default()
-                .name("Wiggler")
-                .board(WIGGLER.compile_string())
-                .build()
-            ,
-            _ => MapBuilder::default()
-                .name("Tutorial")
-                .board(TUTORIAL.compile_string())
-                .build()
-            ,
+    // The cells above, right of, below and left of (x, y) that are on the map
+    pub fn neighbours_4(&self, x: usize, y: usize) -> Neighbours<'_> {
+        Neighbours { map: self, x, y, offsets: &NEIGHBOURS_4, next: 0 }
+    }
+
+    // As neighbours_4, plus the diagonals, clockwise from the top left
+    pub fn neighbours_8(&self, x: usize, y: usize) -> Neighbours<'_> {
+        Neighbours { map: self, x, y, offsets: &NEIGHBOURS_8, next: 0 }
+    }
+
+    // The cells a straight line from one point to another passes through, both
+    // ends included; fails if either end is off the map
+    pub fn cells_on_line(&self, from: (usize, usize), to: (usize, usize)) -> Result<Vec<PositionedCell<'_>>, String> {
+        self.check_bounds(from.0, from.1)?;
+        self.check_bounds(to.0, to.1)?;
+
+        Ok(line_points(from, to).into_iter().map(|(x, y)| ((x, y), &self.board[y][x])).collect())
+    }
+
+    pub fn elevation(&self) -> &[Vec<u8>] {
+        &self.elevation
+    }
+
+    pub fn objects(&self) -> &[Vec<Option<MapObject>>] {
+        &self.objects
+    }
+
+    pub fn set_elevation(&mut self, x: usize, y: usize, height: u8) -> Result<(), String> {
+        self.check_bounds(x, y)?;
+
+        if height > MAX_ELEVATION {
+            return Err(format!("Elevation {} is above the maximum of {}", height, MAX_ELEVATION));
         }
+
+        self.elevation[y][x] = height;
+
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{MapBuilder, CellBuilder, Direction, MapName};
+    pub fn set_object(&mut self, x: usize, y: usize, object: Option<MapObject>) -> Result<(), String> {
+        self.check_bounds(x, y)?;
+        self.objects[y][x] = object;
 
-    #[test]
-    // Nerd Note: The Tutorial Map is 34X12
-    fn length_and_width() {
-        let test = MapBuilder::from_named(None);
+        Ok(())
+    }
 
-        assert!({
-            match test.clone() {
-                Err(ex) => panic!(ex),
-                Ok(map_val) => {
-                    match map_val.length() {
-                        12 => true,
-                        _ => false
-                    }
-                },
-            }
-        });
+    pub fn iter(&self, direction: Direction) -> MapIntoIterator<'_> {
+        MapIntoIterator { map: self, front: 0, back: self.width() * self.length(), direction }
+    }
 
-        assert!({
-            match test.clone() {
-                Err(ex) => panic!(ex),
-                Ok(map_val) => {
-                    match map_val.width() {
-                        34 => true,
-                        _ => false
-                    }
-                },
-            }
-        });
+    // Walks every position row by row, yielding all layers together
+    pub fn layers(&self) -> MapLayerIterator<'_> {
+        MapLayerIterator { cells: self.iter(Direction::Horizontal) }
     }
 
-    #[test]
-    fn invalid_characters_fallback() {
-        let test = CellBuilder::build_string("How Does This Not Exist");
+    pub fn layers_in(&self, direction: Direction) -> MapLayerIterator<'_> {
+        MapLayerIterator { cells: self.iter(direction) }
+    }
 
-        assert!(test.is_err() && test == Err("Invalid Character used o".to_string()));
+    pub fn length(&self) -> usize {
+        self.board.len()
     }
 
-    #[test]
-    fn builder_test() {
+    // 0 for a map with no rows
+    pub fn width(&self) -> usize {
+        self.board.first().map_or(0, Vec::len)
+    }
 
-        let m_test = match MapBuilder::from_named(None) {
-            Ok(map) => map,
-            Err(ex) => panic!(ex),
-        };
+    fn check_bounds(&self, x: usize, y: usize) -> Result<(), String> {
+        if y < self.length() && x < self.width() {
+            Ok(())
+        } else {
+            Err(format!("({}, {}) is outside the {}x{} map", x, y, self.width(), self.length()))
+        }
+    }
 
-        print!("{:?}", m_test);
+    pub fn set_cell(&mut self, x: usize, y: usize, terrain: Terrain) -> Result<(), String> {
+        self.check_bounds(x, y)?;
+        self.board[y][x] = Cell::from(terrain);
+
+        Ok(())
     }
 
-    #[test]
-    fn first_character_from_iterator_test() {
-        let test = MapBuilder::from_named(None).unwrap();
+    // Sets every given position, checking them all before changing anything
+    pub fn paint(&mut self, positions: &[(usize, usize)], terrain: Terrain) -> Result<(), String> {
+        for &(x, y) in positions {
+            self.check_bounds(x, y)?;
+        }
 
-        let test_iter = test.into_iter().next().unwrap();
+        for &(x, y) in positions {
+            self.board[y][x] = Cell::from(terrain);
+        }
 
-        assert!(test_iter.background == 'H');
+        Ok(())
     }
 
-    #[test]
-    #[should_panic]
-    fn all_characters_are_not_h() {
-        let test = MapBuilder::from_named(None).unwrap();
+    // Flood fills the area of matching terrain around (x, y), moving up, down,
+    // left and right. Returns how many cells changed.
+    pub fn fill(&mut self, x: usize, y: usize, terrain: Terrain) -> Result<usize, String> {
+        self.check_bounds(x, y)?;
 
-        for chara in test.into_iter() {
-            assert!(chara.background == 'H');
+        let target = self.board[y][x].terrain();
+
+        if target == terrain {
+            return Ok(0);
+        }
+
+        let mut stack = vec![(x, y)];
+        let mut filled = 0;
+
+        while let Some((cx, cy)) = stack.pop() {
+            if self.board[cy][cx].terrain() != target {
+                continue;
+            }
+
+            self.board[cy][cx] = Cell::from(terrain);
+            filled += 1;
+
+            if cx > 0 { stack.push((cx - 1, cy)); }
+            if cy > 0 { stack.push((cx, cy - 1)); }
+            if cx + 1 < self.width() { stack.push((cx + 1, cy)); }
+            if cy + 1 < self.length() { stack.push((cx, cy + 1)); }
         }
+
+        Ok(filled)
     }
 
-    #[test]
-    fn change_iterator_direction() {
-        let test = MapBuilder::from_named(None).unwrap();
+    // Fills the rectangle between two corners, inclusive
+    pub fn rect(&mut self, from: (usize, usize), to: (usize, usize), terrain: Terrain) -> Result<(), String> {
+        self.check_bounds(from.0, from.1)?;
+        self.check_bounds(to.0, to.1)?;
 
-        let mut test_iter = test.into_iter();
+        for row in &mut self.board[from.1.min(to.1)..=from.1.max(to.1)] {
+            for cell in &mut row[from.0.min(to.0)..=from.0.max(to.0)] {
+                *cell = Cell::from(terrain);
+            }
+        }
 
-        test_iter.change_direction(Direction::Vertical);
+        Ok(())
+    }
 
-        assert!(test_iter.direction() == Direction::Vertical);
+    // Draws a straight line of cells between two points, inclusive
+    pub fn line(&mut self, from: (usize, usize), to: (usize, usize), terrain: Terrain) -> Result<(), String> {
+        self.paint(&line_points(from, to), terrain)
     }
 
-    #[test]
-    fn check_vertical_versus_horizontal_map() {
-        let test = MapBuilder::from_named(None).unwrap();
+    // Inserts a row of rough before the given row; the length is allowed
+    pub fn insert_row(&mut self, y: usize) -> Result<(), String> {
+        if y > self.length() {
+            return Err(format!("Cannot insert row {} into a map {} rows long", y, self.length()));
+        }
 
-        let mut test_iter = test.into_iter();
+        let width = self.width();
+        self.board.insert(y, vec![Cell::from(Terrain::Rough); width]);
+        self.elevation.insert(y, vec![0; width]);
+        self.objects.insert(y, vec![None; width]);
 
-        test_iter.change_direction(Direction::Vertical);
+        Ok(())
+    }
 
-        for x in 1..75 {
-            test_iter.next();
+    pub fn remove_row(&mut self, y: usize) -> Result<Vec<Cell>, String> {
+        if y >= self.length() || self.length() == 1 {
+            return Err(format!("Cannot remove row {} from a map {} rows long", y, self.length()));
         }
 
-        assert!(test_iter.next().unwrap().background == '|' && test_iter.next().unwrap().background == '|');
+        self.elevation.remove(y);
+        self.objects.remove(y);
 
-        test_iter = test.into_iter();
+        Ok(self.board.remove(y))
+    }
 
-        for x in 1..75 {
-            test_iter.next();
+    // Inserts a column of rough before the given column; the width is allowed
+    pub fn insert_column(&mut self, x: usize) -> Result<(), String> {
+        if x > self.width() {
+            return Err(format!("Cannot insert column {} into a map {} columns wide", x, self.width()));
+        }
+
+        for row in &mut self.board {
+            row.insert(x, Cell::from(Terrain::Rough));
+        }
+
+        for row in &mut self.elevation {
+            row.insert(x, 0);
+        }
+
+        for row in &mut self.objects {
+            row.insert(x, None);
         }
 
-        assert!(test_iter.next().unwrap().background == '|' && test_iter.next().unwrap().background == '_');
+        Ok(())
     }
 
-    #[test]
-    fn iterate_horizontally() {
-        let mut idx = 1;
+    pub fn remove_column(&mut self, x: usize) -> Result<Vec<Cell>, String> {
+        if x >= self.width() || self.width() == 1 {
+            return Err(format!("Cannot remove column {} from a map {} columns wide", x, self.width()));
+        }
 
-        for _ in MapBuilder::from_named(None).unwrap().into_iter() { idx = idx + 1; }
+        for row in &mut self.elevation {
+            row.remove(x);
+        }
 
-        assert_eq!(idx, 408);
+        for row in &mut self.objects {
+            row.remove(x);
+        }
+
+        Ok(self.board.iter_mut().map(|row| row.remove(x)).collect())
     }
 
-    #[test]
-    fn iterate_vertically() {
-        let mut idx = 1;
+    // Flips the map left to right
+    pub fn mirror_horizontal(&self) -> Map {
+        let width = self.width();
 
-        let elems = MapBuilder::from_named(None).unwrap();
+        self.transformed(width, self.length(), |x, y| (width - 1 - x, y), Terrain::mirrored)
+    }
 
-        let mut map_iter = elems.into_iter();
+    // Flips the map top to bottom
+    pub fn mirror_vertical(&self) -> Map {
+        let length = self.length();
 
-        map_iter.change_direction(Direction::Vertical);
+        self.transformed(self.width(), length, |x, y| (x, length - 1 - y), Terrain::mirrored)
+    }
 
-        for _ in map_iter { idx = idx + 1; }
+    // Turns the map a quarter turn clockwise
+    pub fn rotate_90(&self) -> Map {
+        let length = self.length();
 
-        assert_eq!(idx, 408);
+        self.transformed(length, self.width(), |x, y| (y, length - 1 - x), |terrain| terrain.transposed().mirrored())
     }
 
-    // Noticed I'd missed '/' ^^;
-    #[test]
-    fn build_all_maps()
+    pub fn rotate_180(&self) -> Map {
+        let (width, length) = (self.width(), self.length());
+
+        self.transformed(width, length, |x, y| (width - 1 - x, length - 1 - y), |terrain| terrain)
+    }
+
+    // Swaps rows and columns, reflecting the map across its top-left to
+    // bottom-right diagonal
+    pub fn transpose(&self) -> Map {
+        self.transformed(self.length(), self.width(), |x, y| (y, x), Terrain::transposed)
+    }
+
+    // Builds a width by length copy where each position takes every layer from
+    // source(x, y) of this map, remapping walls so they still face the right way
+    fn transformed<S, R>(&self, width: usize, length: usize, source: S, remap: R) -> Map
+        where S: Fn(usize, usize) -> (usize, usize), R: Fn(Terrain) -> Terrain
     {
-      let MapDefault = MapBuilder::from_named(None).unwrap();
-      let MapAngled = MapBuilder::from_named(Some(MapName::Angled)).unwrap();
-      let MapDanger = MapBuilder::from_named(Some(MapName::Danger)).unwrap();
-      let MapReverse = MapBuilder::from_named(Some(MapName::Reverse)).unwrap();
-      let MapSpiral = MapBuilder::from_named(Some(MapName::Spiral)).unwrap();
-      let MapWiggler = MapBuilder::from_named(Some(MapName::Wiggler)).unwrap();
+        let positions: Vec<Vec<(usize, usize)>> = (0..length)
+            .map(|y| (0..width).map(|x| source(x, y)).collect())
+            .collect();
+
+        let board = positions.iter().map(|row| row.iter().map(|&(x, y)| {
+            let cell = self.board[y][x];
+
+            if cell.terrain().is_wall() { Cell::from(remap(cell.terrain())) } else { cell }
+        }).collect()).collect();
+
+        Map {
+            name: self.name.clone(),
+            board,
+            metadata: self.metadata.clone(),
+            elevation: positions.iter().map(|row| row.iter().map(|&(x, y)| self.elevation[y][x]).collect()).collect(),
+            objects: positions.iter().map(|row| row.iter().map(|&(x, y)| self.objects[y][x]).collect()).collect(),
+        }
+    }
+
+    // Checks the map is playable: exactly one tee and one hole, no tee, hole or
+    // fairway on the edge of the board, and a path from the tee to the hole
+    // that goes around walls. The ball may cross rough, and may step
+    // diagonally unless that would squeeze through the corner where two walls
+    // meet; slanted walls running alongside the step leave it open.
+    pub fn validate(&self) -> Result<(), String> {
+        let tees = self.find(Terrain::Tee);
+        let holes = self.find(Terrain::Hole);
+
+        if tees.len() != 1 {
+            return Err(format!("{} has {} tees, expected 1", self.name, tees.len()));
+        }
+
+        if holes.len() != 1 {
+            return Err(format!("{} has {} holes, expected 1", self.name, holes.len()));
+        }
+
+        let playable = |x: usize, y: usize| matches!(self.board[y][x].terrain(), Terrain::Tee | Terrain::Fairway | Terrain::Hole);
+        let wall = |x: usize, y: usize| self.board[y][x].terrain().is_wall();
+        let (width, length) = (self.width(), self.length());
+
+        for y in 0..length {
+            for x in 0..width {
+                if (x == 0 || y == 0 || x + 1 == width || y + 1 == length) && playable(x, y) {
+                    return Err(format!("{} has a playable cell on its edge at ({}, {})", self.name, x, y));
+                }
+            }
+        }
+
+        let mut seen = vec![vec![false; width]; length];
+        let mut stack = vec![tees[0]];
+
+        seen[tees[0].1][tees[0].0] = true;
+
+        while let Some((x, y)) = stack.pop() {
+            if (x, y) == holes[0] {
+                return Ok(());
+            }
+
+            let neighbours = (y.saturating_sub(1)..=(y + 1).min(length - 1))
+                .flat_map(|ny| (x.saturating_sub(1)..=(x + 1).min(width - 1)).map(move |nx| (nx, ny)));
+
+            for (nx, ny) in neighbours {
+                let squeezed = nx != x && ny != y && wall(nx, y) && wall(x, ny) && {
+                    // Down-right runs along a backslash, down-left along a slash
+                    let along = if (nx > x) == (ny > y) { Terrain::WallBackslash } else { Terrain::WallSlash };
+
+                    self.board[y][nx].terrain() != along || self.board[ny][x].terrain() != along
+                };
+
+                if !seen[ny][nx] && !wall(nx, ny) && !squeezed {
+                    seen[ny][nx] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+
+        Err(format!("{} has no path from the tee to the hole", self.name))
+    }
+
+    // Every position with the given terrain, row by row
+    pub fn find(&self, terrain: Terrain) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+
+        for (y, row) in self.board.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if cell.terrain() == terrain {
+                    found.push((x, y));
+                }
+            }
+        }
+
+        found
+    }
+
+    // Grows or shrinks the map from the bottom right, padding with rough
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), String> {
+        if width == 0 || height == 0 {
+            return Err(format!("Cannot resize a map to {}x{}", width, height));
+        }
+
+        for row in &mut self.board {
+            row.resize(width, Cell::from(Terrain::Rough));
+        }
+
+        self.board.resize(height, vec![Cell::from(Terrain::Rough); width]);
+
+        for row in &mut self.elevation {
+            row.resize(width, 0);
+        }
+
+        self.elevation.resize(height, vec![0; width]);
+
+        for row in &mut self.objects {
+            row.resize(width, None);
+        }
+
+        self.objects.resize(height, vec![None; width]);
+
+        Ok(())
+    }
+}
+
+// One "key: value" header line. The value has to come back the same from
+// MapBuilder::from_text, so it can't break the line or start or end in spaces.
+fn header_line(key: &str, value: &str) -> Result<String, String> {
+    if value.contains(['\n', '\r']) || value.trim() != value {
+        return Err(format!("Map {} {:?} doesn't fit on a header line", key, value));
+    }
+
+    Ok(format!("{}: {}\n", key, value))
+}
+
+impl Map {
+    // Writes the current versioned map file format, header included. Fails if
+    // the name, author or elevation can't be written so they read back the same.
+    pub fn to_map_file(&self) -> Result<String, String> {
+        let mut header = format!("{}{}\n{}", MAP_FILE_MAGIC, MAP_FILE_VERSION, header_line("name", &self.name)?);
+
+        if let Some(par) = self.metadata.par() {
+            header += &format!("par: {}\n", par);
+        }
+
+        if let Some(author) = self.metadata.author() {
+            header += &header_line("author", author)?;
+        }
+
+        if let Some(difficulty) = self.metadata.difficulty() {
+            header += &format!("difficulty: {}\n", difficulty);
+        }
+
+        if let Some(wind) = self.metadata.wind() {
+            header += &format!("wind: {} {}\n", wind.speed(), wind.angle());
+        }
+
+        let mut text = format!("{}{}\n{}", header, HEADER_END, self);
+
+        if self.elevation.iter().flatten().any(|&height| height > 0) {
+            text += &format!("\n{}", ELEVATION_SECTION);
+
+            for row in &self.elevation {
+                let line = row
+                    .iter()
+                    .map(|&height| {
+                        std::char::from_digit(u32::from(height), 36)
+                            .ok_or_else(|| format!("Elevation {} is above the maximum of {}", height, MAX_ELEVATION))
+                    })
+                    .collect::<Result<String, String>>()?;
+                text += &format!("\n{}", line);
+            }
+        }
+
+        if self.objects.iter().flatten().any(|object| object.is_some()) {
+            text += &format!("\n{}", OBJECTS_SECTION);
+
+            for row in &self.objects {
+                let line: String = row.iter().map(|object| object.map_or(NO_OBJECT, |object| object.glyph())).collect();
+                text += &format!("\n{}", line);
+            }
+        }
+
+        Ok(text)
+    }
+}
+
+// The same glyph format the map files are written in, one line per row, as
+// read back by MapBuilder::from_text
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.board.iter().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+
+            for cell in row {
+                write!(f, "{}", cell.glyph())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Cells on the straight line between two points, using Bresenham's algorithm
+fn line_points(from: (usize, usize), to: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+    let (end_x, end_y) = (to.0 as isize, to.1 as isize);
+    let dx = (end_x - x).abs();
+    let dy = -(end_y - y).abs();
+    let step_x = if x < end_x { 1 } else { -1 };
+    let step_y = if y < end_y { 1 } else { -1 };
+    let mut error = dx + dy;
+    let mut points = Vec::new();
+
+    loop {
+        points.push((x as usize, y as usize));
+
+        if x == end_x && y == end_y {
+            return points;
+        }
+
+        let doubled = 2 * error;
+
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = PositionedCell<'a>;
+    type IntoIter = MapIntoIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter(Direction::Horizontal)
+    }
+}
+
+const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const NEIGHBOURS_8: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0)];
+
+#[derive(Debug, PartialEq)]
+pub struct Neighbours<'a> {
+    map: &'a Map,
+    x: usize,
+    y: usize,
+    offsets: &'static [(isize, isize)],
+    next: usize,
+}
+
+impl<'a> Iterator for Neighbours<'a> {
+    type Item = PositionedCell<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(&(dx, dy)) = self.offsets.get(self.next) {
+            self.next += 1;
+
+            let x = self.x as isize + dx;
+            let y = self.y as isize + dy;
+
+            if x < 0 || y < 0 {
+                continue;
+            }
+
+            if let Some(cell) = self.map.get(x as usize, y as usize) {
+                return Some(((x as usize, y as usize), cell));
+            }
+        }
+
+        None
+    }
+}
+
+// Walks the same positions as a MapIntoIterator, adding the other layers
+#[derive(Debug, PartialEq)]
+pub struct MapLayerIterator<'a> {
+    cells: MapIntoIterator<'a>,
+}
+
+impl<'a> MapLayerIterator<'a> {
+    fn with_layers(&self, ((x, y), cell): PositionedCell<'a>) -> LayeredCell<'a> {
+        ((x, y), cell, self.cells.map.elevation[y][x], self.cells.map.objects[y][x])
+    }
+}
+
+impl<'a> Iterator for MapLayerIterator<'a> {
+    type Item = LayeredCell<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cells.next().map(|cell| self.with_layers(cell))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.cells.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for MapLayerIterator<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.cells.next_back().map(|cell| self.with_layers(cell))
+    }
+}
+
+impl<'a> ExactSizeIterator for MapLayerIterator<'a> {}
+
+// The order a MapIntoIterator visits cells in. Any order can be walked
+// backwards with rev().
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    // Column by column, top to bottom
+    Vertical,
+    // Row by row, left to right
+    Horizontal,
+    // Clockwise around the edge from the top left, then inwards
+    Spiral,
+}
+
+// Yields every cell of a map once, with its position. Cells are numbered in
+// the current direction and the iterator remembers how many it has taken
+// from each end. The direction can only be changed before the first cell is
+// taken, as the cells already taken would not line up with the new order.
+#[derive(Debug, PartialEq)]
+pub struct MapIntoIterator<'a> {
+    map: &'a Map,
+    front: usize,
+    back: usize,
+    direction: Direction,
+}
+
+impl<'a> MapIntoIterator<'a> {
+    pub fn change_direction(&mut self, new_direction: Direction) -> Result<(), String> {
+        if self.front > 0 || self.back < self.map.width() * self.map.length() {
+            return Err("Cannot change direction once iterating has started".to_string());
+        }
+
+        self.direction = new_direction;
+
+        Ok(())
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    // Where the index-th cell in the current direction is
+    fn position(&self, index: usize) -> (usize, usize) {
+        let (width, length) = (self.map.width(), self.map.length());
+
+        match self.direction {
+            Direction::Horizontal => (index % width, index / width),
+            Direction::Vertical => (index / length, index % length),
+            Direction::Spiral => spiral_position(width, length, index),
+        }
+    }
+
+    fn cell(&self, index: usize) -> PositionedCell<'a> {
+        let (x, y) = self.position(index);
+
+        ((x, y), &self.map.board[y][x])
+    }
+}
+
+// Finds the ring the index falls in, then walks its top, right, bottom and
+// left sides in turn
+fn spiral_position(width: usize, length: usize, mut index: usize) -> (usize, usize) {
+    let mut ring = 0;
+
+    loop {
+        let ring_width = width - 2 * ring;
+        let ring_length = length - 2 * ring;
+        let perimeter = if ring_width == 1 || ring_length == 1 {
+            ring_width * ring_length
+        } else {
+            2 * (ring_width + ring_length) - 4
+        };
+
+        if index >= perimeter {
+            index -= perimeter;
+            ring += 1;
+            continue;
+        }
+
+        if index < ring_width {
+            return (ring + index, ring);
+        }
+
+        index -= ring_width;
+
+        if index < ring_length - 1 {
+            return (ring + ring_width - 1, ring + 1 + index);
+        }
+
+        index -= ring_length - 1;
+
+        if index < ring_width - 1 {
+            return (ring + ring_width - 2 - index, ring + ring_length - 1);
+        }
+
+        index -= ring_width - 1;
+
+        return (ring, ring + ring_length - 2 - index);
+    }
+}
+
+impl<'a> Iterator for MapIntoIterator<'a> {
+    type Item = PositionedCell<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        self.front += 1;
+
+        Some(self.cell(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for MapIntoIterator<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        self.back -= 1;
+
+        Some(self.cell(self.back))
+    }
+}
+
+impl<'a> ExactSizeIterator for MapIntoIterator<'a> {}
+
+trait MapCompiler {
+     fn compile_string(&self) -> Result<Vec<Vec<Cell>>, String>;
+}
+
+impl MapCompiler for str {
+     fn compile_string(&self) -> Result<Vec<Vec<Cell>>, String> {
+        let board = self
+            .lines()
+            .map(CellBuilder::build_string)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match board.first() {
+            None => Err("A map needs at least one row".to_string()),
+            Some(first) if first.is_empty() => Err("A map needs at least one column".to_string()),
+            Some(first) => match board.iter().position(|row| row.len() != first.len()) {
+                Some(y) => Err(format!("Row {} is {} wide, expected {}", y, board[y].len(), first.len())),
+                None => Ok(board),
+            },
+        }
+     }
+}
+
+// Parses one extra layer, which must line up with the terrain grid
+fn compile_layer<T, F>(rows: &[&str], width: usize, length: usize, parse: F) -> Result<Vec<Vec<T>>, String>
+    where F: Fn(char) -> Result<T, String>
+{
+    if rows.len() != length {
+        return Err(format!("Layer has {} rows, expected {}", rows.len(), length));
+    }
+
+    rows.iter()
+        .enumerate()
+        .map(|(y, row)| {
+            let cells = row.chars().map(&parse).collect::<Result<Vec<_>, _>>()?;
+
+            if cells.len() == width {
+                Ok(cells)
+            } else {
+                Err(format!("Layer row {} is {} wide, expected {}", y, cells.len(), width))
+            }
+        })
+        .collect()
+}
+
+impl MapBuilder
+{
+    pub fn from_named(map_name : Option<MapName>) -> Result<Map, String> {
+        match map_name {
+            Some(map_val) => {
+                MapBuilder::load_map(map_val)
+            },
+            None => MapBuilder::load_map(MapName::Tutorial),
+        }
+    }
+
+    // Parses a map file in either the versioned or the bare glyph format. A
+    // name in the header takes precedence over the one given here.
+    pub fn from_text(name: &str, text: &str) -> Result<Map, String> {
+        let mut lines = text.lines();
+
+        let version = match lines.next() {
+            Some(first) if first.starts_with(MAP_FILE_MAGIC) => first[MAP_FILE_MAGIC.len()..]
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid map file version line {}", first))?,
+            _ => return MapBuilder::flat(name.to_string(), text.compile_string()?, MapMetadata::default()),
+        };
+
+        if version == 0 || version > MAP_FILE_VERSION {
+            return Err(format!("Unsupported map file version {}", version));
+        }
+
+        let mut name = name.to_string();
+        let mut metadata = MapMetadata::default();
+
+        loop {
+            let line = match lines.next() {
+                Some(line) => line,
+                None => return Err("Map file header is never closed".to_string()),
+            };
+
+            if line == HEADER_END {
+                break;
+            }
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(format!("Map header line is not \"key: value\": {}", line)),
+            };
+
+            match key {
+                "name" => name = value.to_string(),
+                key => metadata.parse_field(key, value)?,
+            }
+        }
+
+        let mut sections = vec![(String::new(), Vec::new())];
+
+        for line in lines {
+            if line.starts_with(HEADER_END) {
+                if version < 2 {
+                    return Err(format!("Version {} map files have no layer sections", version));
+                }
+
+                sections.push((line.to_string(), Vec::new()));
+            } else {
+                sections.last_mut().unwrap().1.push(line);
+            }
+        }
+
+        let mut map = MapBuilder::flat(name, sections[0].1.join("\n").compile_string()?, metadata)?;
+
+        for (section, rows) in &sections[1..] {
+            match section.as_str() {
+                ELEVATION_SECTION => {
+                    map.elevation = compile_layer(rows, map.width(), map.length(), |ch| match ch.to_digit(36) {
+                        Some(height) => Ok(height as u8),
+                        None => Err(format!("Invalid elevation used {}", ch)),
+                    })?
+                },
+                OBJECTS_SECTION => map.objects = compile_layer(rows, map.width(), map.length(), MapObject::from_glyph)?,
+                section => return Err(format!("Unknown map section {}", section)),
+            }
+        }
+
+        Ok(map)
+    }
+
+    // A map with no elevation and no objects
+    fn flat(name: String, board: Vec<Vec<Cell>>, metadata: MapMetadata) -> Result<Map, String> {
+        let (width, length) = (board.first().map_or(0, Vec::len), board.len());
+
+        MapBuilder::default()
+            .name(name)
+            .board(board)
+            .metadata(metadata)
+            .elevation(vec![vec![0; width]; length])
+            .objects(vec![vec![None; width]; length])
+            .build()
+    }
+
+    fn load_map(selected_map: MapName) -> Result<Map, String>{
+        let (name, text) = match selected_map {
+            MapName::Angled => ("Angled", ANGLED),
+            MapName::Danger => ("Danger", DANGER),
+            MapName::Reverse => ("Reverse", REVERSE),
+            MapName::Spiral => ("Spiral", SPIRAL),
+            MapName::Wiggler => ("Wiggler", WIGGLER),
+            MapName::Tutorial => ("Tutorial", TUTORIAL),
+        };
+
+        // The name here is only a fallback for files without a header
+        MapBuilder::from_text(name, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MapBuilder, Cell, CellBuilder, Direction, MapMetadata, MapName, MapObject, Terrain, Wind, TUTORIAL};
+    use std::collections::BTreeSet;
+
+    #[test]
+    // Nerd Note: The Tutorial Map is 34X12
+    fn length_and_width() {
+        let test = MapBuilder::from_named(None);
+
+        assert!({
+            match test.clone() {
+                Err(ex) => panic!("{}", ex),
+                Ok(map_val) => {
+                    map_val.length() == 12
+                },
+            }
+        });
+
+        assert!({
+            match test.clone() {
+                Err(ex) => panic!("{}", ex),
+                Ok(map_val) => {
+                    map_val.width() == 34
+                },
+            }
+        });
+    }
+
+    #[test]
+    fn invalid_characters_fallback() {
+        let test = CellBuilder::build_string("How Does This Not Exist");
+
+        assert!(test.is_err() && test == Err("Invalid Character used o".to_string()));
+    }
+
+    #[test]
+    fn builder_test() {
+
+        let m_test = match MapBuilder::from_named(None) {
+            Ok(map) => map,
+            Err(ex) => panic!("{}", ex),
+        };
+
+        print!("{:?}", m_test);
+    }
+
+    #[test]
+    fn first_character_from_iterator_test() {
+        let test = MapBuilder::from_named(None).unwrap();
+
+        let test_iter = test.into_iter().next().unwrap();
+
+        assert!(test_iter.1.background == 'H');
+    }
+
+    #[test]
+    #[should_panic]
+    fn all_characters_are_not_h() {
+        let test = MapBuilder::from_named(None).unwrap();
+
+        for chara in test.into_iter() {
+            assert!(chara.1.background == 'H');
+        }
+    }
+
+    #[test]
+    fn change_iterator_direction() {
+        let test = MapBuilder::from_named(None).unwrap();
+
+        let mut test_iter = test.into_iter();
+
+        test_iter.change_direction(Direction::Vertical).unwrap();
+
+        assert!(test_iter.direction() == Direction::Vertical);
+    }
+
+    #[test]
+    fn check_vertical_versus_horizontal_map() {
+        let test = MapBuilder::from_named(None).unwrap();
+
+        let mut test_iter = test.into_iter();
+
+        test_iter.change_direction(Direction::Vertical).unwrap();
+
+        for x in 1..75 {
+            test_iter.next();
+        }
+
+        assert!(test_iter.next().unwrap().1.background == '|' && test_iter.next().unwrap().1.background == '|');
+
+        test_iter = test.into_iter();
+
+        for x in 1..75 {
+            test_iter.next();
+        }
+
+        assert!(test_iter.next().unwrap().1.background == '|' && test_iter.next().unwrap().1.background == '_');
+    }
+
+    #[test]
+    fn iterate_horizontally() {
+        let mut idx = 0;
+
+        for _ in MapBuilder::from_named(None).unwrap().into_iter() { idx += 1; }
+
+        assert_eq!(idx, 408);
+    }
+
+    #[test]
+    fn iterate_vertically() {
+        let mut idx = 0;
+
+        let elems = MapBuilder::from_named(None).unwrap();
+
+        let mut map_iter = elems.into_iter();
+
+        map_iter.change_direction(Direction::Vertical).unwrap();
+
+        for _ in map_iter { idx += 1; }
+
+        assert_eq!(idx, 408);
+    }
+
+    // Noticed I'd missed '/' ^^;
+    #[test]
+    fn build_all_maps()
+    {
+      let MapDefault = MapBuilder::from_named(None).unwrap();
+      let MapAngled = MapBuilder::from_named(Some(MapName::Angled)).unwrap();
+      let MapDanger = MapBuilder::from_named(Some(MapName::Danger)).unwrap();
+      let MapReverse = MapBuilder::from_named(Some(MapName::Reverse)).unwrap();
+      let MapSpiral = MapBuilder::from_named(Some(MapName::Spiral)).unwrap();
+      let MapWiggler = MapBuilder::from_named(Some(MapName::Wiggler)).unwrap();
+    }
+
+    #[test]
+    fn to_string_matches_file() {
+        let test = MapBuilder::from_named(None).unwrap();
+
+        assert_eq!(test.to_map_file().unwrap(), TUTORIAL);
+        assert!(TUTORIAL.ends_with(&test.to_string()));
+    }
+
+    #[test]
+    fn set_cell_in_place() {
+        let mut test = MapBuilder::from_named(None).unwrap();
+
+        test.set_cell(8, 4, Terrain::Hole).unwrap();
+
+        assert_eq!(test.board()[4][8].terrain(), Terrain::Hole);
+        assert!(test.set_cell(34, 0, Terrain::Hole).is_err());
+    }
+
+    #[test]
+    fn paint_checks_every_position() {
+        let mut test = MapBuilder::from_named(None).unwrap();
+
+        assert!(test.paint(&[(8, 4), (99, 99)], Terrain::Hole).is_err());
+        assert_eq!(test.board()[4][8].terrain(), Terrain::Fairway);
+
+        test.paint(&[(8, 4), (9, 4)], Terrain::WallSlash).unwrap();
+
+        assert_eq!(test.board()[4][9].glyph(), '/');
+    }
+
+    #[test]
+    fn flood_fill() {
+        let mut test = MapBuilder::from_named(None).unwrap();
+
+        // The fairway and its gap in the bottom wall, minus the inner wall,
+        // tee and hole cells
+        assert_eq!(test.fill(8, 4, Terrain::Rough).unwrap(), 20 * 6 + 2 - 2 - 2);
+        assert_eq!(test.fill(8, 4, Terrain::Rough).unwrap(), 0);
+    }
+
+    #[test]
+    fn rect_and_line_tools() {
+        let mut test = MapBuilder::from_named(None).unwrap();
+
+        test.rect((10, 7), (8, 5), Terrain::Hole).unwrap();
+
+        assert_eq!(test.board()[6][9].terrain(), Terrain::Hole);
+        assert_eq!(test.board()[7][11].terrain(), Terrain::Fairway);
+
+        test.line((0, 0), (4, 2), Terrain::WallBackslash).unwrap();
+
+        let drawn: Vec<char> = [(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)].iter()
+            .map(|&(x, y)| test.board()[y][x].glyph())
+            .collect();
+
+        assert_eq!(drawn, vec!['\\'; 5]);
+        assert_eq!(test.board()[0][1].terrain(), Terrain::Rough);
+        assert!(test.line((0, 0), (40, 2), Terrain::Hole).is_err());
+    }
+
+    #[test]
+    fn insert_and_remove_rows_and_columns() {
+        let mut test = MapBuilder::from_named(None).unwrap();
+
+        test.insert_row(0).unwrap();
+        test.insert_column(34).unwrap();
+
+        assert_eq!((test.width(), test.length()), (35, 13));
+        assert!(test.insert_row(14).is_err());
+
+        test.remove_row(0).unwrap();
+        test.remove_column(34).unwrap();
+
+        assert_eq!(test.to_map_file().unwrap(), TUTORIAL);
+    }
+
+    #[test]
+    fn cannot_remove_last_row() {
+        let mut test = MapBuilder::from_named(None).unwrap();
+
+        test.resize(1, 1).unwrap();
+
+        assert!(test.remove_row(0).is_err());
+        assert!(test.remove_column(0).is_err());
+    }
+
+    #[test]
+    fn resize_pads_with_rough() {
+        let mut test = MapBuilder::from_named(None).unwrap();
+
+        test.resize(40, 14).unwrap();
+
+        assert_eq!((test.width(), test.length()), (40, 14));
+        assert_eq!(test.board()[3][39].terrain(), Terrain::Rough);
+        assert_eq!(test.board()[13][0].terrain(), Terrain::Rough);
+
+        test.resize(10, 4).unwrap();
+
+        assert_eq!(test.to_string(), "HHHHHHHHHH\nHHHHHHHHHH\nHHHHHH|___\nHHHHHH|,,*");
+        assert!(test.resize(0, 4).is_err());
+    }
+
+    #[test]
+    fn to_string_round_trips_every_bundled_map() {
+        for name in MapName::all() {
+            let parsed = MapBuilder::from_named(Some(name)).unwrap();
+            let text = parsed.to_string();
+            let reparsed = MapBuilder::from_text(parsed.name(), &text).unwrap();
+
+            // The bare grid keeps the board; the map file keeps everything
+            assert_eq!(reparsed.board(), parsed.board());
+            assert_eq!(reparsed.to_string(), text);
+            assert_eq!(MapBuilder::from_text("", &parsed.to_map_file().unwrap()).unwrap(), parsed);
+        }
+    }
+
+    #[test]
+    fn to_string_round_trips_edited_maps() {
+        let mut test = MapBuilder::from_named(Some(MapName::Spiral)).unwrap();
+
+        test.resize(30, 20).unwrap();
+        test.line((0, 19), (29, 0), Terrain::WallSlash).unwrap();
+        test.set_cell(2, 2, Terrain::Tee).unwrap();
+
+        assert_eq!(MapBuilder::from_text("Spiral", &test.to_string()).unwrap().board(), test.board());
+        assert_eq!(MapBuilder::from_text("", &test.to_map_file().unwrap()).unwrap(), test);
+    }
+
+    #[test]
+    fn from_text_rejects_bad_grids() {
+        assert_eq!(MapBuilder::from_text("Empty", ""), Err("A map needs at least one row".to_string()));
+        assert_eq!(MapBuilder::from_text("Ragged", "HHH\nHH"), Err("Row 1 is 2 wide, expected 3".to_string()));
+        assert!(MapBuilder::from_text("Invalid", "HHH\nHoH").is_err());
+    }
+
+    #[test]
+    fn bare_format_still_loads() {
+        let bare = TUTORIAL.split_once("---\n").unwrap().1;
+        let test = MapBuilder::from_text("Bare", bare).unwrap();
+
+        assert_eq!(test.name(), "Bare");
+        assert_eq!(test.board(), MapBuilder::from_named(None).unwrap().board());
+        assert_eq!(test.metadata().par(), None);
+    }
+
+    #[test]
+    fn header_metadata_is_surfaced() {
+        let text = "GolfMania map v2\nname: Windy\npar: 3\nauthor: Someone Else\ndifficulty: 4\nwind: 2.5 90\n---\nHHH\nH*H\nH#H";
+        let test = MapBuilder::from_text("Fallback", text).unwrap();
+
+        assert_eq!(test.name(), "Windy");
+        assert_eq!(test.metadata().par(), Some(3));
+        assert_eq!(test.metadata().author().map(|author| author.as_str()), Some("Someone Else"));
+        assert_eq!(test.metadata().difficulty(), Some(4));
+        assert_eq!(test.metadata().wind(), Some(Wind::new(2.5, 90.0)));
+        assert_eq!(test.length(), 3);
+        assert_eq!(test.to_map_file().unwrap(), text);
+    }
+
+    #[test]
+    fn metadata_round_trips() {
+        let mut test = MapBuilder::from_named(Some(MapName::Danger)).unwrap();
+
+        test.metadata_mut().set_par(Some(4));
+        test.metadata_mut().set_wind(Some(Wind::new(1.0, 270.0)));
+
+        assert_eq!(MapBuilder::from_text("", &test.to_map_file().unwrap()).unwrap(), test);
+    }
+
+    #[test]
+    fn bundled_maps_have_metadata() {
+        for name in MapName::all() {
+            let test = MapBuilder::from_named(Some(name)).unwrap();
+
+            assert!(test.metadata().par().is_some(), "{} has no par", test.name());
+            assert_eq!(test.metadata().author().map(|author| author.as_str()), Some("Andrew Sansom"));
+            assert!(test.metadata().difficulty().is_some(), "{} has no difficulty", test.name());
+        }
+
+        let level = MapBuilder::from_text("", include_str!("Maps/Level1.txt")).unwrap();
+
+        assert_eq!(level.name(), "Level1");
+        assert_eq!(level.board(), MapBuilder::from_named(Some(MapName::Angled)).unwrap().board());
+    }
+
+    #[test]
+    fn unwritable_maps_are_rejected() {
+        let mut test = MapBuilder::from_named(None).unwrap();
+
+        test.metadata_mut().set_author(Some("Mallory\n---\nHHH".to_string()));
+
+        assert_eq!(test.to_map_file(), Err("Map author \"Mallory\\n---\\nHHH\" doesn't fit on a header line".to_string()));
+
+        test.metadata_mut().set_author(Some(" Padded".to_string()));
+
+        assert!(test.to_map_file().is_err());
+
+        let test = MapBuilder::default()
+            .name("Broken\r".to_string())
+            .board(vec![vec![Cell::from(Terrain::Rough)]])
+            .metadata(MapMetadata::default())
+            .elevation(vec![vec![0]])
+            .objects(vec![vec![None]])
+            .build()
+            .unwrap();
+
+        assert!(test.to_map_file().unwrap_err().starts_with("Map name"));
+
+        let test = MapBuilder::default()
+            .name("Tall".to_string())
+            .board(vec![vec![Cell::from(Terrain::Rough)]])
+            .metadata(MapMetadata::default())
+            .elevation(vec![vec![36]])
+            .objects(vec![vec![None]])
+            .build()
+            .unwrap();
+
+        assert_eq!(test.to_map_file(), Err("Elevation 36 is above the maximum of 35".to_string()));
+    }
+
+    #[test]
+    fn bad_headers_are_rejected() {
+        assert_eq!(MapBuilder::from_text("", "GolfMania map v9\n---\nH"), Err("Unsupported map file version 9".to_string()));
+        assert_eq!(MapBuilder::from_text("", "GolfMania map v1\npar: 3"), Err("Map file header is never closed".to_string()));
+        assert_eq!(MapBuilder::from_text("", "GolfMania map v1\ncolour: red\n---\nH"), Err("Unknown map header field colour".to_string()));
+        assert!(MapBuilder::from_text("", "GolfMania map v1\nwind: 3\n---\nH").is_err());
+        assert!(MapBuilder::from_text("", "GolfMania map v1\npar: three\n---\nH").is_err());
+    }
+
+    const LAYERED: &str = "GolfMania map v2\nname: Layered\n---\n|__|\n|*#|\n--- elevation\n0000\n01a0\n--- objects\n....\n.WB.";
+
+    #[test]
+    fn layers_load_from_sections() {
+        let test = MapBuilder::from_text("", LAYERED).unwrap();
+
+        assert_eq!(test.elevation()[1], vec![0, 1, 10, 0]);
+        assert_eq!(test.objects()[1][1], Some(MapObject::Windmill));
+        assert_eq!(test.objects()[1][2], Some(MapObject::Bumper));
+        assert_eq!(test.to_map_file().unwrap(), LAYERED);
+    }
+
+    #[test]
+    fn layers_iterate_together() {
+        let test = MapBuilder::from_text("", LAYERED).unwrap();
+        let layers: Vec<_> = test.layers().collect();
+
+        assert_eq!(layers.len(), 8);
+        assert_eq!(layers[0].0, (0, 0));
+
+        let ((x, y), cell, height, object) = layers[6];
+
+        assert_eq!((x, y), (2, 1));
+        assert_eq!(cell.terrain(), Terrain::Hole);
+        assert_eq!(height, 10);
+        assert_eq!(object, Some(MapObject::Bumper));
+    }
+
+    #[test]
+    fn older_files_load_flat() {
+        let test = MapBuilder::from_text("", "GolfMania map v1\n---\nH*#H").unwrap();
+
+        assert_eq!(test.elevation(), &[vec![0; 4]][..]);
+        assert!(test.objects()[0].iter().all(|object| object.is_none()));
+        assert!(MapBuilder::from_text("", "GolfMania map v1\n---\nH\n--- elevation\n1").is_err());
+    }
+
+    #[test]
+    fn layers_must_line_up() {
+        assert_eq!(MapBuilder::from_text("", "GolfMania map v2\n---\nHH\n--- elevation\n1"), Err("Layer row 0 is 1 wide, expected 2".to_string()));
+        assert_eq!(MapBuilder::from_text("", "GolfMania map v2\n---\nHH\n--- objects\n..\n.."), Err("Layer has 2 rows, expected 1".to_string()));
+        assert_eq!(MapBuilder::from_text("", "GolfMania map v2\n---\nHH\n--- objects\n.X"), Err("Invalid object used X".to_string()));
+        assert_eq!(MapBuilder::from_text("", "GolfMania map v2\n---\nHH\n--- water\n.."), Err("Unknown map section --- water".to_string()));
+    }
+
+    #[test]
+    fn edits_keep_layers_aligned() {
+        let mut test = MapBuilder::from_text("", LAYERED).unwrap();
+
+        test.insert_row(0).unwrap();
+        test.insert_column(0).unwrap();
+
+        assert_eq!(test.elevation()[2][3], 10);
+        assert_eq!(test.objects()[2][2], Some(MapObject::Windmill));
+
+        test.remove_column(0).unwrap();
+        test.remove_row(0).unwrap();
+        test.resize(6, 3).unwrap();
+
+        assert_eq!(test.elevation().len(), 3);
+        assert!(test.objects().iter().all(|row| row.len() == 6));
+        assert_eq!(test.layers().count(), 18);
+    }
+
+    #[test]
+    fn set_layer_cells() {
+        let mut test = MapBuilder::from_named(None).unwrap();
+
+        test.set_elevation(8, 4, 35).unwrap();
+        test.set_object(9, 4, Some(MapObject::Windmill)).unwrap();
+
+        assert!(test.set_elevation(8, 4, 36).is_err());
+        assert!(test.set_object(34, 4, None).is_err());
+        assert_eq!(MapBuilder::from_text("", &test.to_map_file().unwrap()).unwrap(), test);
+    }
+
+    #[test]
+    fn validate_bundled_maps() {
+        for name in MapName::all() {
+            assert_eq!(MapBuilder::from_named(Some(name)).unwrap().validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn validate_walls_block() {
+        let fenced = MapBuilder::from_text("Fenced", "HHH|HHH\nH*H|H#H\nHHH|HHH").unwrap();
+
+        assert_eq!(fenced.validate(), Err("Fenced has no path from the tee to the hole".to_string()));
+
+        // Nor can the ball squeeze between diagonal walls
+        let slanted = MapBuilder::from_text("Slanted", "\\HHHH\nH\\H#H\nHH\\HH\nH*H\\H\nHHHH\\").unwrap();
+
+        assert_eq!(slanted.validate(), Err("Slanted has no path from the tee to the hole".to_string()));
+
+        // Rough is no obstacle
+        let open = MapBuilder::from_text("Open", "HHHHHHH\nH*HHH#H\nHHHHHHH").unwrap();
+
+        assert_eq!(open.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_tees_holes_and_edges() {
+        let mut test = MapBuilder::from_named(None).unwrap();
+
+        test.set_cell(9, 3, Terrain::Fairway).unwrap();
+
+        assert_eq!(test.validate(), Err("Tutorial has 0 tees, expected 1".to_string()));
+
+        test.set_cell(9, 3, Terrain::Tee).unwrap();
+        test.set_cell(9, 4, Terrain::Hole).unwrap();
+
+        assert_eq!(test.validate(), Err("Tutorial has 2 holes, expected 1".to_string()));
+
+        test.set_cell(9, 4, Terrain::Fairway).unwrap();
+        test.line((14, 9), (14, 11), Terrain::Fairway).unwrap();
+
+        assert_eq!(test.validate(), Err("Tutorial has a playable cell on its edge at (14, 11)".to_string()));
+    }
+
+    #[test]
+    fn mirrored_walls_face_the_other_way() {
+        let angled = MapBuilder::from_named(Some(MapName::Angled)).unwrap();
+        let mirrored = angled.mirror_horizontal();
+        let width = angled.width();
+
+        assert_eq!(angled.board()[4][14].terrain(), Terrain::WallBackslash);
+        assert_eq!(mirrored.board()[4][width - 1 - 14].terrain(), Terrain::WallSlash);
+        assert_eq!(mirrored.board()[3][width - 1 - 6].terrain(), Terrain::WallVertical);
+        assert_eq!(mirrored.validate(), Ok(()));
+        assert_eq!(angled.mirror_vertical().board()[angled.length() - 1 - 4][14].terrain(), Terrain::WallSlash);
+    }
+
+    #[test]
+    fn transposed_walls_swap_orientation() {
+        let angled = MapBuilder::from_named(Some(MapName::Angled)).unwrap();
+        let transposed = angled.transpose();
+
+        assert_eq!((transposed.width(), transposed.length()), (angled.length(), angled.width()));
+        assert_eq!(transposed.board()[6][3].terrain(), Terrain::WallHorizontal);
+        assert_eq!(transposed.board()[7][2].terrain(), Terrain::WallVertical);
+        assert_eq!(transposed.board()[14][4].terrain(), Terrain::WallBackslash);
+        assert_eq!(transposed.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rotations_compose() {
+        let angled = MapBuilder::from_named(Some(MapName::Angled)).unwrap();
+        let quarter = angled.rotate_90();
+
+        assert_eq!(quarter, angled.transpose().mirror_horizontal());
+        assert_eq!(quarter.rotate_90(), angled.rotate_180());
+        assert_eq!(angled.rotate_180(), angled.mirror_horizontal().mirror_vertical());
+        assert_eq!(quarter.rotate_90().rotate_90().rotate_90(), angled);
+        assert_eq!(quarter.board()[0][angled.length() - 1].terrain(), angled.board()[0][0].terrain());
+        assert_eq!(quarter.validate(), Ok(()));
+    }
+
+    #[test]
+    fn transforms_are_involutions() {
+        for name in MapName::all() {
+            let test = MapBuilder::from_named(Some(name)).unwrap();
+
+            assert_eq!(test.mirror_horizontal().mirror_horizontal(), test);
+            assert_eq!(test.mirror_vertical().mirror_vertical(), test);
+            assert_eq!(test.transpose().transpose(), test);
+        }
+    }
+
+    #[test]
+    fn transforms_carry_layers() {
+        let test = MapBuilder::from_text("", LAYERED).unwrap().rotate_90();
+
+        assert_eq!((test.width(), test.length()), (2, 4));
+        assert_eq!(test.elevation()[2][0], 10);
+        assert_eq!(test.objects()[1][0], Some(MapObject::Windmill));
+        assert_eq!(test.board()[0][0].terrain(), Terrain::WallHorizontal);
+    }
+
+    #[test]
+    fn random_access() {
+        let test = MapBuilder::from_named(None).unwrap();
+
+        assert_eq!(test.get(9, 3).map(|cell| cell.terrain()), Some(Terrain::Tee));
+        assert_eq!(test.get(34, 3), None);
+        assert_eq!(test.get(0, 12), None);
+    }
+
+    #[test]
+    fn empty_maps_are_0_by_0() {
+        let test = MapBuilder::default()
+            .name("Empty".to_string())
+            .board(Vec::new())
+            .metadata(MapMetadata::default())
+            .elevation(Vec::new())
+            .objects(Vec::new())
+            .build()
+            .unwrap();
+
+        assert_eq!((test.width(), test.length()), (0, 0));
+        assert_eq!(test.get(0, 0), None);
+        assert!(test.column(0).is_none());
+        assert_eq!(test.neighbours_8(0, 0).count(), 0);
+        assert!(test.cells_on_line((0, 0), (1, 1)).is_err());
+        assert_eq!(test.iter(Direction::Spiral).count(), 0);
+        assert!(test.validate().is_err());
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let test = MapBuilder::from_named(None).unwrap();
+        let row = test.row(2).unwrap();
+        let column = test.column(6).unwrap();
+
+        assert_eq!(row.len(), 34);
+        assert_eq!(row[7].terrain(), Terrain::WallHorizontal);
+        assert_eq!(column.len(), 12);
+        assert!(column[2..10].iter().all(|cell| cell.terrain() == Terrain::WallVertical));
+        assert!(test.row(12).is_none());
+        assert!(test.column(34).is_none());
+    }
+
+    #[test]
+    fn neighbourhoods() {
+        let test = MapBuilder::from_named(None).unwrap();
+        let around_tee: Vec<_> = test.neighbours_4(9, 3).map(|(position, _)| position).collect();
+
+        assert_eq!(around_tee, vec![(9, 2), (10, 3), (9, 4), (8, 3)]);
+        assert_eq!(test.neighbours_8(9, 3).count(), 8);
+        assert_eq!(test.neighbours_8(9, 3).next().unwrap().1.terrain(), Terrain::WallHorizontal);
+    }
+
+    #[test]
+    fn neighbourhoods_stop_at_edges() {
+        let test = MapBuilder::from_named(None).unwrap();
+
+        assert_eq!(test.neighbours_4(0, 0).count(), 2);
+        assert_eq!(test.neighbours_8(0, 0).count(), 3);
+        assert_eq!(test.neighbours_8(33, 5).count(), 5);
+        assert_eq!(test.neighbours_8(33, 11).map(|(position, _)| position).collect::<Vec<_>>(), vec![(32, 10), (33, 10), (32, 11)]);
+    }
+
+    #[test]
+    fn find_terrain() {
+        let test = MapBuilder::from_named(None).unwrap();
+
+        assert_eq!(test.find(Terrain::Tee), vec![(9, 3)]);
+        assert_eq!(test.find(Terrain::Hole), vec![(23, 4)]);
+        assert_eq!(test.find(Terrain::WallSlash), vec![]);
+    }
+
+    #[test]
+    fn line_of_cells() {
+        let test = MapBuilder::from_named(None).unwrap();
+        let line = test.cells_on_line((9, 3), (23, 4)).unwrap();
+
+        assert_eq!(line.len(), 15);
+        assert_eq!(line[0].1.terrain(), Terrain::Tee);
+        assert_eq!(line[14].1.terrain(), Terrain::Hole);
+        assert!(line.iter().any(|&(_, cell)| cell.terrain() == Terrain::WallVertical));
+        assert_eq!(test.cells_on_line((4, 4), (4, 4)).unwrap().len(), 1);
+        assert!(test.cells_on_line((9, 3), (40, 4)).is_err());
+    }
+
+    #[test]
+    fn iterators_yield_positions() {
+        let test = MapBuilder::from_named(None).unwrap();
+        let cells: Vec<_> = test.iter(Direction::Horizontal).collect();
+
+        assert_eq!(cells.len(), 34 * 12);
+        assert_eq!(cells[34].0, (0, 1));
+        assert_eq!(cells.last().unwrap().0, (33, 11));
+        assert_eq!(test.iter(Direction::Vertical).nth(12).unwrap().0, (1, 0));
+        assert_eq!(test.iter(Direction::Vertical).next_back().unwrap().0, (33, 11));
+    }
+
+    #[test]
+    fn iterators_run_backwards() {
+        let test = MapBuilder::from_named(None).unwrap();
+        let forwards: Vec<_> = test.iter(Direction::Vertical).map(|(position, _)| position).collect();
+        let mut backwards: Vec<_> = test.iter(Direction::Vertical).rev().map(|(position, _)| position).collect();
+
+        backwards.reverse();
+
+        assert_eq!(forwards, backwards);
+        assert_eq!(test.iter(Direction::Horizontal).rev().nth(1).unwrap().0, (32, 11));
+    }
+
+    #[test]
+    fn iterators_know_their_length() {
+        let test = MapBuilder::from_named(None).unwrap();
+        let mut cells = test.iter(Direction::Spiral);
+
+        assert_eq!(cells.len(), 408);
+
+        cells.next();
+        cells.next_back();
+
+        assert_eq!(cells.len(), 406);
+        assert_eq!(cells.by_ref().count(), 406);
+        assert_eq!(cells.next(), None);
+        assert_eq!(cells.next_back(), None);
+        assert_eq!(test.layers().rev().len(), 408);
+    }
+
+    #[test]
+    fn spiral_visits_every_cell_once() {
+        for &(width, length) in &[(34, 12), (1, 1), (1, 5), (5, 1), (4, 4), (5, 3), (3, 6)] {
+            let mut test = MapBuilder::from_text("", "H").unwrap();
+
+            test.resize(width, length).unwrap();
+
+            let positions: Vec<_> = test.iter(Direction::Spiral).map(|(position, _)| position).collect();
+            let unique: BTreeSet<_> = positions.iter().cloned().collect();
+
+            assert_eq!(positions.len(), width * length);
+            assert_eq!(unique.len(), width * length);
+
+            // Every step moves to a side-by-side cell
+            for pair in positions.windows(2) {
+                let ((ax, ay), (bx, by)) = (pair[0], pair[1]);
+
+                assert_eq!((ax as isize - bx as isize).abs() + (ay as isize - by as isize).abs(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn spiral_goes_clockwise() {
+        let mut test = MapBuilder::from_text("", "H").unwrap();
+
+        test.resize(3, 3).unwrap();
+
+        let positions: Vec<_> = test.iter(Direction::Spiral).map(|(position, _)| position).collect();
+
+        assert_eq!(positions, vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (1, 2), (0, 2), (0, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn direction_is_fixed_once_started() {
+        let test = MapBuilder::from_named(None).unwrap();
+
+        for &direction in &[Direction::Horizontal, Direction::Vertical, Direction::Spiral] {
+            let mut cells = test.iter(Direction::Horizontal);
+
+            cells.change_direction(Direction::Spiral).unwrap();
+            cells.change_direction(direction).unwrap();
+
+            let mut positions: Vec<_> = cells.by_ref().take(14).map(|(position, _)| position).collect();
+
+            assert!(cells.change_direction(Direction::Vertical).is_err());
+
+            let last = cells.next_back().unwrap().0;
+
+            assert!(cells.change_direction(Direction::Horizontal).is_err());
+            assert_eq!(cells.direction(), direction);
+
+            // Neither end repeats or skips a cell
+            positions.extend(cells.map(|(position, _)| position));
+            positions.push(last);
+
+            let unique: BTreeSet<_> = positions.iter().cloned().collect();
+
+            assert_eq!(positions.len(), 408);
+            assert_eq!(unique.len(), 408);
+        }
+    }
+
+    #[test]
+    fn layers_follow_direction() {
+        let test = MapBuilder::from_text("", LAYERED).unwrap();
+        let ((x, y), cell, height, _) = test.layers_in(Direction::Vertical).nth(5).unwrap();
+
+        assert_eq!((x, y), (2, 1));
+        assert_eq!(cell.terrain(), Terrain::Hole);
+        assert_eq!(height, 10);
     }
 }
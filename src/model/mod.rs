@@ -1,4 +1,9 @@
 pub mod map;
+pub mod generator;
+pub mod course;
 pub mod score_card;
+pub mod scoring;
 pub mod player;
+pub mod team;
+pub mod tournament;
 pub mod view_model;
\ No newline at end of file
@@ -1,16 +1,210 @@
-pub const ANON: &'static str = "The great unknown; anon";
+use std::collections::BTreeMap;
 
-pub struct Player{
-    _name: &'static str
+use model::score_card::ScoreCard;
+
+pub const ANON: &str = "The great unknown; anon";
+
+// World Handicap System style "bonus for excellence" multiplier
+const HANDICAP_MULTIPLIER: f32 = 0.96;
+// Only the most recent rounds count towards the handicap index
+const HANDICAP_WINDOW: usize = 20;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Player {
+    _id: u32,
+    _name: String,
+    _profile: PlayerProfile,
 }
 
 impl Player {
-    pub fn new(name: Option<&'static str>) -> Player {
+    pub fn new(id: u32, name: Option<String>) -> Player {
         Player {
+            _id: id,
             _name: match name {
                 Some(actual_name) => actual_name,
-                None => ANON,
+                None => ANON.to_string(),
             },
+            _profile: PlayerProfile::default(),
         }
     }
-}
\ No newline at end of file
+
+    pub fn id(&self) -> u32 {
+        self._id
+    }
+
+    pub fn name(&self) -> &String {
+        &self._name
+    }
+
+    pub fn rename(&mut self, name: String) {
+        self._name = name;
+    }
+
+    pub fn profile(&self) -> &PlayerProfile {
+        &self._profile
+    }
+
+    pub fn record_round(&mut self, card: ScoreCard) {
+        self._profile.record(card);
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerProfile {
+    _rounds: Vec<ScoreCard>,
+}
+
+impl PlayerProfile {
+    pub fn record(&mut self, card: ScoreCard) {
+        self._rounds.push(card);
+    }
+
+    pub fn rounds(&self) -> &[ScoreCard] {
+        &self._rounds
+    }
+
+    pub fn rounds_played(&self) -> usize {
+        self._rounds.len()
+    }
+
+    pub fn average_score(&self, map: &str) -> Option<f32> {
+        let scores: Vec<i32> = self._rounds
+            .iter()
+            .filter(|card| card.map() == map)
+            .map(|card| card.score())
+            .collect();
+
+        match scores.len() {
+            0 => None,
+            played => Some(scores.iter().sum::<i32>() as f32 / played as f32),
+        }
+    }
+
+    pub fn average_scores(&self) -> BTreeMap<String, f32> {
+        let mut totals: BTreeMap<String, (i32, usize)> = BTreeMap::new();
+
+        for card in &self._rounds {
+            let entry = totals.entry(card.map().clone()).or_insert((0, 0));
+            entry.0 += card.score();
+            entry.1 += 1;
+        }
+
+        totals
+            .into_iter()
+            .map(|(map, (total, played))| (map, total as f32 / played as f32))
+            .collect()
+    }
+
+    // Best round relative to par, so rounds on different maps compare fairly.
+    // Rounds recorded without a par can't be compared and are skipped.
+    pub fn best_round(&self) -> Option<&ScoreCard> {
+        self._rounds.iter().filter(|card| card.has_par()).min_by_key(|card| card.differential())
+    }
+
+    // Averages the best differentials out of the most recent rounds with a par,
+    // the number counted scaling with how many there are. Needs at least 3.
+    pub fn handicap_index(&self) -> Option<f32> {
+        let rated: Vec<&ScoreCard> = self._rounds.iter().filter(|card| card.has_par()).collect();
+
+        let counted = match rated.len().min(HANDICAP_WINDOW) {
+            0..=2 => return None,
+            3..=5 => 1,
+            6..=8 => 2,
+            9..=11 => 3,
+            12..=14 => 4,
+            15..=16 => 5,
+            17..=18 => 6,
+            19 => 7,
+            _ => 8,
+        };
+
+        let recent = &rated[rated.len().saturating_sub(HANDICAP_WINDOW)..];
+
+        let mut differentials: Vec<i32> = recent.iter().map(|card| card.differential()).collect();
+        differentials.sort();
+
+        let average = differentials[..counted].iter().sum::<i32>() as f32 / counted as f32;
+
+        Some((average * HANDICAP_MULTIPLIER * 10f32).round() / 10f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Player, ANON};
+    use model::score_card::ScoreCard;
+
+    fn card(map: &str, par: i32, score: i32) -> ScoreCard {
+        let mut card = ScoreCard::new("testMan".to_string(), map.to_string(), score);
+        card.set_par(par);
+        card
+    }
+
+    #[test]
+    fn anonymous_player() {
+        let test = Player::new(1, None);
+
+        assert_eq!(test.id(), 1);
+        assert_eq!(test.name(), ANON);
+    }
+
+    #[test]
+    fn owned_names() {
+        let input = String::from("Runtime Name");
+        let mut test = Player::new(2, Some(input));
+
+        assert_eq!(test.name(), "Runtime Name");
+
+        test.rename("Renamed".to_string());
+
+        assert_eq!(test.name(), "Renamed");
+    }
+
+    #[test]
+    fn profile_statistics() {
+        let mut test = Player::new(3, None);
+
+        test.record_round(card("Tutorial", 3, 4));
+        test.record_round(card("Tutorial", 3, 2));
+        test.record_round(card("Spiral", 5, 9));
+
+        let profile = test.profile();
+
+        assert_eq!(profile.rounds_played(), 3);
+        assert_eq!(profile.average_score("Tutorial"), Some(3f32));
+        assert_eq!(profile.average_score("Danger"), None);
+        assert_eq!(profile.average_scores().get("Spiral"), Some(&9f32));
+        assert_eq!(profile.best_round().unwrap().score(), 2);
+    }
+
+    #[test]
+    fn handicap_index() {
+        let mut test = Player::new(4, None);
+
+        test.record_round(card("Tutorial", 3, 6));
+        test.record_round(card("Tutorial", 3, 5));
+
+        assert_eq!(test.profile().handicap_index(), None);
+
+        test.record_round(card("Angled", 4, 6));
+
+        // Best of three: +2, scaled by 0.96
+        assert_eq!(test.profile().handicap_index(), Some(1.9));
+    }
+
+    #[test]
+    fn rounds_without_par_are_not_rated() {
+        let mut test = Player::new(5, None);
+
+        test.record_round(ScoreCard::new("testMan".to_string(), "Tutorial".to_string(), 1));
+        test.record_round(card("Tutorial", 3, 5));
+        test.record_round(card("Tutorial", 3, 6));
+
+        assert_eq!(test.profile().best_round().unwrap().score(), 5);
+        assert_eq!(test.profile().handicap_index(), None);
+
+        test.record_round(card("Angled", 4, 7));
+
+        assert_eq!(test.profile().handicap_index(), Some(1.9));
+    }
+}
@@ -1,8 +1,14 @@
-#[derive(Debug, Default)]
+use std::cmp::Ordering;
+
+use model::course::Course;
+
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct ScoreCard {
     _name: String,
     _map: String,
-    _score: i32
+    _score: i32,
+    _par: i32,
+    _strokes: Vec<i32>,
 }
 
 impl ScoreCard {
@@ -11,17 +17,142 @@ impl ScoreCard {
             _name : name,
             _map : map,
             _score : score,
+            _par : 0,
+            _strokes : Vec::new(),
         }
     }
 
+    // A card for a full round, one entry in strokes per hole of the course
+    pub fn from_strokes(name: String, course: &Course, strokes: Vec<i32>) -> ScoreCard {
+        ScoreCard {
+            _name : name,
+            _map : course.name().clone(),
+            _score : strokes.iter().sum(),
+            _par : course.par(),
+            _strokes : strokes,
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self._name
+    }
+
+    pub fn map(&self) -> &String {
+        &self._map
+    }
+
+    pub fn score(&self) -> i32 {
+        self._score
+    }
+
+    pub fn strokes(&self) -> &[i32] {
+        &self._strokes
+    }
+
+    pub fn gross(&self) -> i32 {
+        self._score
+    }
+
+    pub fn par(&self) -> i32 {
+        self._par
+    }
+
+    pub fn set_par(&mut self, par: i32) {
+        self._par = par;
+    }
+
+    // No hole has a par of 0, so 0 means the card was made without one
+    pub fn has_par(&self) -> bool {
+        self._par > 0
+    }
+
+    // Strokes over (or under, if negative) par; with no par set this is the raw score
+    pub fn differential(&self) -> i32 {
+        self._score - self._par
+    }
+
+    // Fails unless there is a score for each hole of the course
+    pub fn expect_every_hole(&self, course: &Course) -> Result<(), String> {
+        if self._strokes.len() != course.holes().len() {
+            return Err(format!(
+                "{} has scores for {} holes but {} has {}",
+                self._name, self._strokes.len(), course.name(), course.holes().len()
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Needs a score for every hole, as handicap strokes are given hole by hole
+    pub fn net_strokes(&self, course: &Course, handicap: f32) -> Result<Vec<i32>, String> {
+        self.expect_every_hole(course)?;
+
+        Ok(self._strokes
+            .iter()
+            .zip(course.strokes_received(handicap))
+            .map(|(strokes, received)| strokes - received)
+            .collect())
+    }
+
+    pub fn net(&self, course: &Course, handicap: f32) -> Result<i32, String> {
+        Ok(self.net_strokes(course, handicap)?.iter().sum())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeaderboardEntry {
+    _name: String,
+    _gross: i32,
+    _net: i32,
+}
+
+impl LeaderboardEntry {
     pub fn name(&self) -> &String {
         &self._name
     }
+
+    pub fn gross(&self) -> i32 {
+        self._gross
+    }
+
+    pub fn net(&self) -> i32 {
+        self._net
+    }
+}
+
+// Ranks each card, paired with its player's handicap index, by net then gross score
+pub fn leaderboard(course: &Course, entries: &[(&ScoreCard, f32)]) -> Result<Vec<LeaderboardEntry>, String> {
+    let mut board = entries
+        .iter()
+        .map(|&(card, handicap)| Ok(LeaderboardEntry {
+            _name: card.name().clone(),
+            _gross: card.gross(),
+            _net: card.net(course, handicap)?,
+        }))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    board.sort_by(|left, right| match left._net.cmp(&right._net) {
+        Ordering::Equal => left._gross.cmp(&right._gross),
+        by_net => by_net,
+    });
+
+    Ok(board)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ScoreCard;
+    use super::{leaderboard, ScoreCard};
+    use model::course::{Course, Hole};
+
+    fn test_course() -> Course {
+        Course::new(
+            "Test".to_string(),
+            vec![
+                Hole::new("Tutorial".to_string(), 3, 2),
+                Hole::new("Danger".to_string(), 4, 1),
+            ]
+        ).unwrap()
+    }
 
     #[test]
     fn it_works() {
@@ -29,4 +160,50 @@ mod tests {
 
         assert_eq!(test._name, "testMan");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn differential_against_par() {
+        let mut test = ScoreCard::new("testMan".to_string(), "testMap".to_string(), 5);
+
+        assert_eq!(test.differential(), 5);
+
+        test.set_par(3);
+
+        assert_eq!(test.differential(), 2);
+    }
+
+    #[test]
+    fn net_score_from_handicap() {
+        let course = test_course();
+        let test = ScoreCard::from_strokes("testMan".to_string(), &course, vec![5, 6]);
+
+        assert_eq!(test.gross(), 11);
+        assert_eq!(test.par(), 7);
+        assert_eq!(test.net_strokes(&course, 3f32), Ok(vec![4, 4]));
+        assert_eq!(test.net(&course, 3f32), Ok(8));
+    }
+
+    #[test]
+    fn leaderboard_orders_by_net() {
+        let course = test_course();
+        let veteran = ScoreCard::from_strokes("Veteran".to_string(), &course, vec![3, 4]);
+        let beginner = ScoreCard::from_strokes("Beginner".to_string(), &course, vec![5, 5]);
+
+        let test = leaderboard(&course, &[(&veteran, 0f32), (&beginner, 4f32)]).unwrap();
+
+        assert_eq!(test[0].name(), "Beginner");
+        assert_eq!(test[0].gross(), 10);
+        assert_eq!(test[0].net(), 6);
+        assert_eq!(test[1].net(), 7);
+    }
+
+    #[test]
+    fn net_needs_every_hole() {
+        let course = test_course();
+        let total_only = ScoreCard::new("testMan".to_string(), "Test".to_string(), 9);
+        let full = ScoreCard::from_strokes("Full".to_string(), &course, vec![5, 5]);
+
+        assert_eq!(total_only.net(&course, 0f32), Err("testMan has scores for 0 holes but Test has 2".to_string()));
+        assert!(leaderboard(&course, &[(&full, 0f32), (&total_only, 0f32)]).is_err());
+    }
+}
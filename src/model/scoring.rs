@@ -0,0 +1,293 @@
+use model::course::Course;
+use model::score_card::ScoreCard;
+
+// A way of turning per-hole strokes into a result, so the same cards can be
+// played as stroke play, match play, Stableford or skins
+pub trait ScoringFormat {
+    type Outcome;
+    fn score(&self, course: &Course, cards: &[&ScoreCard]) -> Result<Self::Outcome, String>;
+}
+
+// Holes to score, after checking every card has a score for each hole of the
+// course. With no cards there is nothing to score.
+fn holes_played(course: &Course, cards: &[&ScoreCard]) -> Result<usize, String> {
+    for card in cards {
+        card.expect_every_hole(course)?;
+    }
+
+    Ok(if cards.is_empty() { 0 } else { course.holes().len() })
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HoleResult {
+    Won,
+    Lost,
+    Halved,
+}
+
+// Match play between two cards, from the point of view of the first
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MatchPlay;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchPlayResult {
+    _holes: Vec<HoleResult>,
+    _dormie_at: Option<usize>,
+    _closed_out_at: Option<usize>,
+}
+
+impl MatchPlayResult {
+    pub fn holes(&self) -> &[HoleResult] {
+        &self._holes
+    }
+
+    pub fn won(&self) -> usize {
+        self._holes.iter().filter(|&&hole| hole == HoleResult::Won).count()
+    }
+
+    pub fn lost(&self) -> usize {
+        self._holes.iter().filter(|&&hole| hole == HoleResult::Lost).count()
+    }
+
+    pub fn halved(&self) -> usize {
+        self._holes.iter().filter(|&&hole| hole == HoleResult::Halved).count()
+    }
+
+    // Holes up, negative when the first player is behind
+    pub fn up(&self) -> i32 {
+        self.won() as i32 - self.lost() as i32
+    }
+
+    // First hole after which the leader was up by exactly the holes remaining
+    pub fn dormie_at(&self) -> Option<usize> {
+        self._dormie_at
+    }
+
+    // Hole on which the match was decided early, no holes are scored after it
+    pub fn closed_out_at(&self) -> Option<usize> {
+        self._closed_out_at
+    }
+}
+
+impl ScoringFormat for MatchPlay {
+    type Outcome = MatchPlayResult;
+
+    fn score(&self, course: &Course, cards: &[&ScoreCard]) -> Result<MatchPlayResult, String> {
+        if cards.len() != 2 {
+            return Err(format!("Match play needs exactly 2 cards, got {}", cards.len()));
+        }
+
+        let total = course.holes().len();
+        let mut result = MatchPlayResult::default();
+
+        for hole in 0..holes_played(course, cards)? {
+            let ours = cards[0].strokes()[hole];
+            let theirs = cards[1].strokes()[hole];
+
+            result._holes.push(
+                if ours < theirs {
+                    HoleResult::Won
+                } else if ours > theirs {
+                    HoleResult::Lost
+                } else {
+                    HoleResult::Halved
+                }
+            );
+
+            let lead = result.up().unsigned_abs() as usize;
+            let remaining = total - (hole + 1);
+
+            if lead > remaining {
+                result._closed_out_at = Some(hole + 1);
+                break;
+            }
+
+            if lead == remaining && remaining > 0 && result._dormie_at.is_none() {
+                result._dormie_at = Some(hole + 1);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+// Points per hole: 2 for par, one more for each stroke under, one fewer for
+// each stroke over, never below zero. Outcome is each card's total, in order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stableford;
+
+impl Stableford {
+    pub fn points(par: i32, strokes: i32) -> i32 {
+        (2 + par - strokes).max(0)
+    }
+}
+
+impl ScoringFormat for Stableford {
+    type Outcome = Vec<i32>;
+
+    fn score(&self, course: &Course, cards: &[&ScoreCard]) -> Result<Vec<i32>, String> {
+        holes_played(course, cards)?;
+
+        Ok(
+            cards
+                .iter()
+                .map(|card| {
+                    card.strokes()
+                        .iter()
+                        .zip(course.holes())
+                        .map(|(&strokes, hole)| Stableford::points(hole.par(), strokes))
+                        .sum()
+                })
+                .collect()
+        )
+    }
+}
+
+// A skin goes to the outright lowest score on a hole; tied holes carry their
+// skin over to the next hole
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Skins;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SkinsResult {
+    _skins: Vec<usize>,
+    _hole_winners: Vec<Option<usize>>,
+    _carried_over: usize,
+}
+
+impl SkinsResult {
+    // Skins won by each card, in the order the cards were given
+    pub fn skins(&self) -> &[usize] {
+        &self._skins
+    }
+
+    // Index of the card that won each hole outright
+    pub fn hole_winners(&self) -> &[Option<usize>] {
+        &self._hole_winners
+    }
+
+    // Skins still unclaimed after the last hole
+    pub fn carried_over(&self) -> usize {
+        self._carried_over
+    }
+}
+
+impl ScoringFormat for Skins {
+    type Outcome = SkinsResult;
+
+    fn score(&self, course: &Course, cards: &[&ScoreCard]) -> Result<SkinsResult, String> {
+        let mut result = SkinsResult {
+            _skins: vec![0; cards.len()],
+            _hole_winners: Vec::new(),
+            _carried_over: 0,
+        };
+
+        for hole in 0..holes_played(course, cards)? {
+            let best = cards.iter().map(|card| card.strokes()[hole]).min().unwrap();
+            let leaders: Vec<usize> = (0..cards.len())
+                .filter(|&idx| cards[idx].strokes()[hole] == best)
+                .collect();
+
+            result._carried_over += 1;
+
+            if leaders.len() == 1 {
+                result._skins[leaders[0]] += result._carried_over;
+                result._carried_over = 0;
+                result._hole_winners.push(Some(leaders[0]));
+            } else {
+                result._hole_winners.push(None);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HoleResult, MatchPlay, ScoringFormat, Skins, Stableford};
+    use model::course::{Course, Hole};
+    use model::score_card::ScoreCard;
+
+    fn test_course(holes: usize) -> Course {
+        Course::new(
+            "Test".to_string(),
+            (0..holes).map(|idx| Hole::new(format!("Hole {}", idx + 1), 3, idx + 1)).collect()
+        ).unwrap()
+    }
+
+    fn card(course: &Course, strokes: Vec<i32>) -> ScoreCard {
+        ScoreCard::from_strokes("testMan".to_string(), course, strokes)
+    }
+
+    #[test]
+    fn match_play_needs_two_cards() {
+        let course = test_course(3);
+        let only = card(&course, vec![3, 3, 3]);
+
+        assert!(MatchPlay.score(&course, &[&only]).is_err());
+    }
+
+    #[test]
+    fn match_play_dormie_and_closed_out() {
+        let course = test_course(5);
+        let ours = card(&course, vec![2, 3, 2, 3, 3]);
+        let theirs = card(&course, vec![3, 3, 3, 3, 3]);
+
+        let test = MatchPlay.score(&course, &[&ours, &theirs]).unwrap();
+
+        assert_eq!(test.holes(), &[HoleResult::Won, HoleResult::Halved, HoleResult::Won, HoleResult::Halved]);
+        assert_eq!(test.up(), 2);
+        assert_eq!(test.halved(), 2);
+        assert_eq!(test.dormie_at(), Some(3));
+        assert_eq!(test.closed_out_at(), Some(4));
+    }
+
+    #[test]
+    fn match_play_all_square() {
+        let course = test_course(2);
+        let ours = card(&course, vec![2, 4]);
+        let theirs = card(&course, vec![3, 3]);
+
+        let test = MatchPlay.score(&course, &[&ours, &theirs]).unwrap();
+
+        assert_eq!(test.up(), 0);
+        assert_eq!(test.won(), 1);
+        assert_eq!(test.lost(), 1);
+        assert_eq!(test.closed_out_at(), None);
+    }
+
+    #[test]
+    fn stableford_points() {
+        let course = test_course(3);
+        let test = card(&course, vec![2, 3, 6]);
+
+        assert_eq!(Stableford.score(&course, &[&test]).unwrap(), vec![5]);
+    }
+
+    #[test]
+    fn every_format_needs_every_hole() {
+        let course = test_course(3);
+        let full = card(&course, vec![3, 3, 3]);
+        let partial = card(&course, vec![2, 2]);
+        let expected = Some("testMan has scores for 2 holes but Test has 3".to_string());
+
+        assert_eq!(MatchPlay.score(&course, &[&full, &partial]).err(), expected);
+        assert_eq!(Stableford.score(&course, &[&partial]).err(), expected);
+        assert_eq!(Skins.score(&course, &[&partial, &full]).err(), expected);
+    }
+
+    #[test]
+    fn skins_carry_over() {
+        let course = test_course(4);
+        let first = card(&course, vec![3, 2, 3, 3]);
+        let second = card(&course, vec![3, 3, 2, 3]);
+        let third = card(&course, vec![3, 3, 3, 3]);
+
+        let test = Skins.score(&course, &[&first, &second, &third]).unwrap();
+
+        assert_eq!(test.skins(), &[2, 1, 0]);
+        assert_eq!(test.hole_winners(), &[None, Some(0), Some(1), None]);
+        assert_eq!(test.carried_over(), 1);
+    }
+}
@@ -0,0 +1,333 @@
+use model::course::Course;
+use model::player::Player;
+use model::score_card::ScoreCard;
+
+// Board coordinates, (x, y)
+pub type Position = (usize, usize);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TeamFormat {
+    // Everyone hits from the team ball, which is then moved to the best shot
+    Scramble,
+    // Everyone plays their own ball, the lowest score on each hole counts
+    BestBall,
+    // One ball, the players take turns hitting it
+    AlternateShot,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Team {
+    _name: String,
+    _players: Vec<Player>,
+}
+
+impl Team {
+    // A player can only be on the team once, as each plays their own shot
+    pub fn new(name: String, players: Vec<Player>) -> Result<Team, String> {
+        for (idx, player) in players.iter().enumerate() {
+            if players[..idx].iter().any(|other| other.id() == player.id()) {
+                return Err(format!("Player {} is on team {} more than once", player.id(), name));
+            }
+        }
+
+        Ok(Team {
+            _name: name,
+            _players: players,
+        })
+    }
+
+    pub fn name(&self) -> &String {
+        &self._name
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self._players
+    }
+
+    fn has_player(&self, id: u32) -> bool {
+        self._players.iter().any(|player| player.id() == id)
+    }
+}
+
+// A team working its way round a course in one of the team formats
+#[derive(Clone, Debug, PartialEq)]
+pub struct TeamSession {
+    _team: Team,
+    _format: TeamFormat,
+    _course: Course,
+    _strokes: Vec<i32>,
+    _current: i32,
+    _ball: Option<Position>,
+    _turn: usize,
+}
+
+impl TeamSession {
+    pub fn new(team: Team, format: TeamFormat, course: Course) -> Result<TeamSession, String> {
+        if team.players().is_empty() {
+            return Err(format!("Team {} has no players", team.name()));
+        }
+
+        Ok(
+            TeamSession {
+                _team: team,
+                _format: format,
+                _course: course,
+                _strokes: Vec::new(),
+                _current: 0,
+                _ball: None,
+                _turn: 0,
+            }
+        )
+    }
+
+    pub fn team(&self) -> &Team {
+        &self._team
+    }
+
+    pub fn format(&self) -> TeamFormat {
+        self._format
+    }
+
+    // Index into the course of the hole being played
+    pub fn hole(&self) -> usize {
+        self._strokes.len()
+    }
+
+    // Where the team ball lies on the current hole, None before the tee shot
+    pub fn ball(&self) -> Option<Position> {
+        self._ball
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.hole() >= self._course.holes().len()
+    }
+
+    // Whose turn it is in alternate shot; the rotation runs on across holes,
+    // so whoever didn't hole out tees off next
+    pub fn next_player(&self) -> Option<&Player> {
+        match self._format {
+            TeamFormat::AlternateShot if !self.is_complete() => {
+                let players = self._team.players();
+                Some(&players[self._turn % players.len()])
+            }
+            _ => None,
+        }
+    }
+
+    pub fn alternate_shot(&mut self, player_id: u32, landed: Position, target: Position) -> Result<(), String> {
+        self.expect_format(TeamFormat::AlternateShot)?;
+
+        let expected = match self.next_player() {
+            Some(player) => player.id(),
+            None => return Err("Round is already complete".to_string()),
+        };
+
+        if expected != player_id {
+            return Err(format!("It is player {}'s turn, not {}", expected, player_id));
+        }
+
+        self._turn += 1;
+        self.stroke(landed, target);
+
+        Ok(())
+    }
+
+    // Every player hits from the team ball; it moves to whichever shot finished
+    // nearest the target, which is returned
+    pub fn scramble(&mut self, shots: &[(u32, Position)], target: Position) -> Result<Position, String> {
+        self.expect_format(TeamFormat::Scramble)?;
+        self.expect_in_progress()?;
+
+        if shots.len() != self._team.players().len() {
+            return Err(format!("Expected {} shots, got {}", self._team.players().len(), shots.len()));
+        }
+
+        self.expect_each_player_once(&shots.iter().map(|&(id, _)| id).collect::<Vec<_>>())?;
+
+        let best = shots
+            .iter()
+            .map(|&(_, position)| position)
+            .min_by_key(|&position| distance_squared(position, target))
+            .unwrap();
+
+        self.stroke(best, target);
+
+        Ok(best)
+    }
+
+    // Each player's strokes for the current hole; the lowest is the team score
+    pub fn best_ball(&mut self, scores: &[(u32, i32)]) -> Result<i32, String> {
+        self.expect_format(TeamFormat::BestBall)?;
+        self.expect_in_progress()?;
+
+        self.expect_each_player_once(&scores.iter().map(|&(id, _)| id).collect::<Vec<_>>())?;
+
+        if let Some(player) = self._team.players().iter().find(|player| !scores.iter().any(|&(id, _)| id == player.id())) {
+            return Err(format!("No score for player {}", player.id()));
+        }
+
+        let best = scores.iter().map(|&(_, strokes)| strokes).min().unwrap();
+
+        self._strokes.push(best);
+
+        Ok(best)
+    }
+
+    pub fn score_card(&self) -> ScoreCard {
+        ScoreCard::from_strokes(self._team.name().clone(), &self._course, self._strokes.clone())
+    }
+
+    // Every entry must come from a team member, and no member may appear twice
+    fn expect_each_player_once(&self, ids: &[u32]) -> Result<(), String> {
+        for (idx, &id) in ids.iter().enumerate() {
+            if !self._team.has_player(id) {
+                return Err(format!("Player {} is not on team {}", id, self._team.name()));
+            }
+
+            if ids[..idx].contains(&id) {
+                return Err(format!("Player {} played more than once", id));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn stroke(&mut self, landed: Position, target: Position) {
+        self._current += 1;
+
+        if landed == target {
+            self._strokes.push(self._current);
+            self._current = 0;
+            self._ball = None;
+        } else {
+            self._ball = Some(landed);
+        }
+    }
+
+    fn expect_format(&self, format: TeamFormat) -> Result<(), String> {
+        if self._format == format {
+            Ok(())
+        } else {
+            Err(format!("Session is playing {:?}, not {:?}", self._format, format))
+        }
+    }
+
+    fn expect_in_progress(&self) -> Result<(), String> {
+        if self.is_complete() {
+            Err("Round is already complete".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn distance_squared(from: Position, to: Position) -> usize {
+    let dx = from.0.max(to.0) - from.0.min(to.0);
+    let dy = from.1.max(to.1) - from.1.min(to.1);
+
+    dx * dx + dy * dy
+}
+
+// Team score per hole from individual cards, the lowest score on each hole counting
+pub fn best_ball_strokes(cards: &[&ScoreCard]) -> Vec<i32> {
+    let holes = cards.iter().map(|card| card.strokes().len()).min().unwrap_or(0);
+
+    (0..holes)
+        .map(|hole| cards.iter().map(|card| card.strokes()[hole]).min().unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{best_ball_strokes, Team, TeamFormat, TeamSession};
+    use model::course::{Course, Hole};
+    use model::player::Player;
+    use model::score_card::ScoreCard;
+
+    fn test_course() -> Course {
+        Course::new(
+            "Test".to_string(),
+            vec![
+                Hole::new("Tutorial".to_string(), 3, 1),
+                Hole::new("Danger".to_string(), 3, 2),
+            ]
+        ).unwrap()
+    }
+
+    fn test_team() -> Team {
+        Team::new("Office".to_string(), vec![Player::new(1, None), Player::new(2, None)]).unwrap()
+    }
+
+    #[test]
+    fn empty_team() {
+        let team = Team::new("Nobody".to_string(), Vec::new()).unwrap();
+
+        assert!(TeamSession::new(team, TeamFormat::Scramble, test_course()).is_err());
+    }
+
+    #[test]
+    fn players_join_once() {
+        assert_eq!(
+            Team::new("Twins".to_string(), vec![Player::new(1, None), Player::new(2, None), Player::new(1, None)]),
+            Err("Player 1 is on team Twins more than once".to_string())
+        );
+    }
+
+    #[test]
+    fn scramble_takes_best_position() {
+        let mut test = TeamSession::new(test_team(), TeamFormat::Scramble, test_course()).unwrap();
+
+        assert_eq!(test.scramble(&[(1, (3, 3)), (2, (8, 4))], (10, 4)), Ok((8, 4)));
+        assert_eq!(test.ball(), Some((8, 4)));
+        assert_eq!(test.scramble(&[(1, (10, 4)), (2, (9, 4))], (10, 4)), Ok((10, 4)));
+        assert_eq!(test.hole(), 1);
+        assert!(test.scramble(&[(1, (1, 1)), (3, (1, 1))], (10, 4)).is_err());
+        assert!(test.best_ball(&[(1, 3), (2, 3)]).is_err());
+    }
+
+    #[test]
+    fn best_ball_lowest_counts() {
+        let mut test = TeamSession::new(test_team(), TeamFormat::BestBall, test_course()).unwrap();
+
+        assert!(test.best_ball(&[(1, 4)]).is_err());
+        assert_eq!(test.best_ball(&[(1, 4), (2, 2)]), Ok(2));
+        assert_eq!(test.best_ball(&[(1, 3), (2, 5)]), Ok(3));
+        assert!(test.is_complete());
+        assert_eq!(test.score_card().gross(), 5);
+    }
+
+    #[test]
+    fn alternate_shot_rotates() {
+        let mut test = TeamSession::new(test_team(), TeamFormat::AlternateShot, test_course()).unwrap();
+
+        assert_eq!(test.next_player().unwrap().id(), 1);
+        assert!(test.alternate_shot(2, (5, 5), (9, 9)).is_err());
+        assert!(test.alternate_shot(1, (5, 5), (9, 9)).is_ok());
+        assert!(test.alternate_shot(2, (9, 9), (9, 9)).is_ok());
+
+        // Player 2 holed out, so player 1 tees off on the next hole
+        assert_eq!(test.next_player().unwrap().id(), 1);
+        assert_eq!(test.score_card().strokes(), &[2]);
+    }
+
+    #[test]
+    fn best_ball_from_cards() {
+        let course = test_course();
+        let first = ScoreCard::from_strokes("First".to_string(), &course, vec![3, 5]);
+        let second = ScoreCard::from_strokes("Second".to_string(), &course, vec![4, 2]);
+
+        assert_eq!(best_ball_strokes(&[&first, &second]), vec![3, 2]);
+    }
+
+    #[test]
+    fn strangers_and_repeats_are_rejected() {
+        let mut test = TeamSession::new(test_team(), TeamFormat::BestBall, test_course()).unwrap();
+
+        assert_eq!(test.best_ball(&[(1, 4), (2, 5), (99, 1)]), Err("Player 99 is not on team Office".to_string()));
+        assert_eq!(test.best_ball(&[(1, 4), (2, 5), (1, 1)]), Err("Player 1 played more than once".to_string()));
+
+        let mut test = TeamSession::new(test_team(), TeamFormat::Scramble, test_course()).unwrap();
+
+        assert_eq!(test.scramble(&[(1, (3, 3)), (1, (8, 4))], (10, 4)), Err("Player 1 played more than once".to_string()));
+    }
+}
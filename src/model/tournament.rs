@@ -0,0 +1,461 @@
+use std::collections::BTreeMap;
+
+use model::course::Course;
+use model::player::Player;
+use model::score_card::ScoreCard;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cut {
+    _after_round: usize,
+    _top: usize,
+}
+
+impl Cut {
+    // Keep the top players (and anyone tied with the last of them) after the given round
+    pub fn new(after_round: usize, top: usize) -> Cut {
+        Cut {
+            _after_round: after_round,
+            _top: top,
+        }
+    }
+
+    pub fn after_round(&self) -> usize {
+        self._after_round
+    }
+
+    pub fn top(&self) -> usize {
+        self._top
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    _player_id: u32,
+    _name: String,
+    _rounds: Vec<i32>,
+    _total: i32,
+    _position: usize,
+    _made_cut: bool,
+}
+
+impl Standing {
+    pub fn player_id(&self) -> u32 {
+        self._player_id
+    }
+
+    pub fn name(&self) -> &String {
+        &self._name
+    }
+
+    pub fn rounds(&self) -> &[i32] {
+        &self._rounds
+    }
+
+    pub fn total(&self) -> i32 {
+        self._total
+    }
+
+    // Shared by tied players, so two players on 1 are followed by 3
+    pub fn position(&self) -> usize {
+        self._position
+    }
+
+    pub fn made_cut(&self) -> bool {
+        self._made_cut
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tournament {
+    _name: String,
+    _course: Course,
+    _rounds: usize,
+    _cut: Option<Cut>,
+    _players: Vec<Player>,
+    _cards: BTreeMap<u32, Vec<ScoreCard>>,
+    _completed_rounds: usize,
+    _eliminated: Vec<u32>,
+    _playoff: Vec<u32>,
+    _winner: Option<u32>,
+}
+
+impl Tournament {
+    pub fn new(name: String, course: Course, rounds: usize, cut: Option<Cut>) -> Result<Tournament, String> {
+        if rounds == 0 {
+            return Err("A tournament needs at least one round".to_string());
+        }
+
+        if let Some(cut) = cut {
+            if cut.after_round() == 0 || cut.after_round() >= rounds {
+                return Err(format!("Cut after round {} is outside a {} round event", cut.after_round(), rounds));
+            }
+        }
+
+        Ok(
+            Tournament {
+                _name: name,
+                _course: course,
+                _rounds: rounds,
+                _cut: cut,
+                _players: Vec::new(),
+                _cards: BTreeMap::new(),
+                _completed_rounds: 0,
+                _eliminated: Vec::new(),
+                _playoff: Vec::new(),
+                _winner: None,
+            }
+        )
+    }
+
+    pub fn name(&self) -> &String {
+        &self._name
+    }
+
+    pub fn course(&self) -> &Course {
+        &self._course
+    }
+
+    pub fn rounds(&self) -> usize {
+        self._rounds
+    }
+
+    // The round currently being played, counting from 1
+    pub fn current_round(&self) -> Option<usize> {
+        if self._completed_rounds < self._rounds {
+            Some(self._completed_rounds + 1)
+        } else {
+            None
+        }
+    }
+
+    pub fn register(&mut self, player: Player) -> Result<(), String> {
+        if self._completed_rounds > 0 || !self._cards.is_empty() {
+            return Err("Registration closes once the first round starts".to_string());
+        }
+
+        if self._players.iter().any(|existing| existing.id() == player.id()) {
+            return Err(format!("Player {} is already registered", player.id()));
+        }
+
+        self._players.push(player);
+
+        Ok(())
+    }
+
+    // Players still in the event, i.e. everyone who hasn't missed the cut
+    pub fn field(&self) -> Vec<&Player> {
+        self._players
+            .iter()
+            .filter(|player| !self._eliminated.contains(&player.id()))
+            .collect()
+    }
+
+    pub fn submit(&mut self, player_id: u32, card: ScoreCard) -> Result<(), String> {
+        let round = match self.current_round() {
+            Some(round) => round,
+            None => return Err("All rounds have been played".to_string()),
+        };
+
+        if !self.field().iter().any(|player| player.id() == player_id) {
+            return Err(format!("Player {} is not in the field", player_id));
+        }
+
+        if card.map() != self._course.name() {
+            return Err(format!("Card is for {}, not {}", card.map(), self._course.name()));
+        }
+
+        if card.strokes().len() != self._course.holes().len() {
+            return Err(format!("Card has {} holes, {} has {}", card.strokes().len(), self._course.name(), self._course.holes().len()));
+        }
+
+        let cards = self._cards.entry(player_id).or_default();
+
+        if cards.len() >= round {
+            return Err(format!("Player {} already has a card for round {}", player_id, round));
+        }
+
+        cards.push(card);
+
+        Ok(())
+    }
+
+    // Closes the current round once every player in the field has a card,
+    // applying the cut if it falls after this round
+    pub fn complete_round(&mut self) -> Result<(), String> {
+        let round = match self.current_round() {
+            Some(round) => round,
+            None => return Err("All rounds have been played".to_string()),
+        };
+
+        if let Some(player) = self.field().iter().find(|player| self.cards_for(player.id()).len() < round) {
+            return Err(format!("Player {} has no card for round {}", player.id(), round));
+        }
+
+        self._completed_rounds = round;
+
+        if let Some(cut) = self._cut {
+            if cut.after_round() == round {
+                self.apply_cut(cut);
+            }
+        }
+
+        if self.current_round().is_none() {
+            self._playoff = self.leaders();
+
+            if self._playoff.len() == 1 {
+                self._winner = self._playoff.pop();
+            }
+        }
+
+        Ok(())
+    }
+
+    // Players tied for the lead after the final round, still to play off
+    pub fn playoff_contenders(&self) -> &[u32] {
+        &self._playoff
+    }
+
+    // Sudden death: everyone still in the playoff plays a hole, anyone beaten
+    // by the best score drops out. Returns the winner once there is one.
+    pub fn playoff_hole(&mut self, scores: &[(u32, i32)]) -> Result<Option<u32>, String> {
+        if self._playoff.is_empty() {
+            return Err("There is no playoff to play".to_string());
+        }
+
+        // Exactly one score from each player still in the playoff
+        for (idx, &(id, _)) in scores.iter().enumerate() {
+            if !self._playoff.contains(&id) {
+                return Err(format!("Player {} is not in the playoff", id));
+            }
+
+            if scores[..idx].iter().any(|&(scored, _)| scored == id) {
+                return Err(format!("Player {} played more than once", id));
+            }
+        }
+
+        if let Some(id) = self._playoff.iter().find(|&&id| !scores.iter().any(|&(scored, _)| scored == id)) {
+            return Err(format!("No playoff score for player {}", id));
+        }
+
+        let best = scores
+            .iter()
+            .map(|&(_, strokes)| strokes)
+            .min()
+            .unwrap();
+
+        self._playoff.retain(|id| scores.iter().any(|&(scored, strokes)| scored == *id && strokes == best));
+
+        if self._playoff.len() == 1 {
+            self._winner = self._playoff.pop();
+        }
+
+        Ok(self._winner)
+    }
+
+    pub fn winner(&self) -> Option<u32> {
+        self._winner
+    }
+
+    // Aggregated results, those who made the cut first, then by total strokes.
+    // A playoff winner is moved to the top of the players tied with them.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self._players
+            .iter()
+            .map(|player| {
+                let rounds: Vec<i32> = self.cards_for(player.id()).iter().map(|card| card.gross()).collect();
+
+                Standing {
+                    _player_id: player.id(),
+                    _name: player.name().clone(),
+                    _total: rounds.iter().sum(),
+                    _rounds: rounds,
+                    _position: 0,
+                    _made_cut: !self._eliminated.contains(&player.id()),
+                }
+            })
+            .collect();
+
+        let winner = self._winner;
+
+        standings.sort_by_key(|standing| (!standing._made_cut, standing._total, Some(standing._player_id) != winner));
+
+        for idx in 0..standings.len() {
+            standings[idx]._position = if idx > 0 && Tournament::tied(&standings[idx - 1], &standings[idx], winner) {
+                standings[idx - 1]._position
+            } else {
+                idx + 1
+            };
+        }
+
+        standings
+    }
+
+    fn tied(previous: &Standing, current: &Standing, winner: Option<u32>) -> bool {
+        previous._made_cut == current._made_cut
+            && previous._total == current._total
+            && Some(previous._player_id) != winner
+    }
+
+    fn cards_for(&self, player_id: u32) -> &[ScoreCard] {
+        match self._cards.get(&player_id) {
+            Some(cards) => cards,
+            None => &[],
+        }
+    }
+
+    fn total_for(&self, player_id: u32) -> i32 {
+        self.cards_for(player_id).iter().map(|card| card.gross()).sum()
+    }
+
+    fn apply_cut(&mut self, cut: Cut) {
+        let mut totals: Vec<i32> = self.field().iter().map(|player| self.total_for(player.id())).collect();
+        totals.sort();
+
+        let line = match totals.get(cut.top().saturating_sub(1)) {
+            Some(&line) if cut.top() > 0 => line,
+            _ => return,
+        };
+
+        let missed: Vec<u32> = self.field()
+            .iter()
+            .map(|player| player.id())
+            .filter(|&id| self.total_for(id) > line)
+            .collect();
+
+        self._eliminated.extend(missed);
+    }
+
+    fn leaders(&self) -> Vec<u32> {
+        let best = self.field().iter().map(|player| self.total_for(player.id())).min();
+
+        self.field()
+            .iter()
+            .map(|player| player.id())
+            .filter(|&id| Some(self.total_for(id)) == best)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cut, Tournament};
+    use model::course::{Course, Hole};
+    use model::player::Player;
+    use model::score_card::ScoreCard;
+
+    fn test_course() -> Course {
+        Course::new("Test".to_string(), vec![Hole::new("Tutorial".to_string(), 3, 1)]).unwrap()
+    }
+
+    fn card(strokes: i32) -> ScoreCard {
+        ScoreCard::from_strokes("testMan".to_string(), &test_course(), vec![strokes])
+    }
+
+    fn play_round(test: &mut Tournament, scores: &[(u32, i32)]) {
+        for &(id, strokes) in scores {
+            test.submit(id, card(strokes)).unwrap();
+        }
+
+        test.complete_round().unwrap();
+    }
+
+    fn test_tournament() -> Tournament {
+        let mut test = Tournament::new("Open".to_string(), test_course(), 2, Some(Cut::new(1, 2))).unwrap();
+
+        for id in 1..5 {
+            test.register(Player::new(id, Some(format!("Player {}", id)))).unwrap();
+        }
+
+        test
+    }
+
+    #[test]
+    fn invalid_cut() {
+        assert!(Tournament::new("Open".to_string(), test_course(), 2, Some(Cut::new(2, 2))).is_err());
+    }
+
+    #[test]
+    fn registration() {
+        let mut test = test_tournament();
+
+        assert!(test.register(Player::new(1, None)).is_err());
+
+        test.submit(1, card(3)).unwrap();
+
+        assert!(test.register(Player::new(9, None)).is_err());
+    }
+
+    #[test]
+    fn round_needs_every_card() {
+        let mut test = test_tournament();
+
+        test.submit(1, card(3)).unwrap();
+
+        assert!(test.submit(1, card(3)).is_err());
+        assert!(test.complete_round().is_err());
+    }
+
+    #[test]
+    fn cut_keeps_ties() {
+        let mut test = test_tournament();
+
+        play_round(&mut test, &[(1, 2), (2, 3), (3, 3), (4, 5)]);
+
+        assert_eq!(test.field().len(), 3);
+        assert!(test.submit(4, card(2)).is_err());
+
+        play_round(&mut test, &[(1, 5), (2, 3), (3, 4)]);
+
+        let standings = test.standings();
+
+        assert_eq!(test.current_round(), None);
+        assert_eq!(standings[0].player_id(), 2);
+        assert_eq!(standings[0].position(), 1);
+        assert_eq!(standings[1].position(), 2);
+        assert_eq!(standings[2].position(), 2);
+        assert_eq!(standings[2].total(), 7);
+        assert!(!standings[3].made_cut());
+        assert_eq!(standings[3].rounds(), &[5]);
+        assert_eq!(test.winner(), Some(2));
+    }
+
+    #[test]
+    fn playoff_decides_tie() {
+        let mut test = test_tournament();
+
+        play_round(&mut test, &[(1, 3), (2, 3), (3, 4), (4, 4)]);
+        play_round(&mut test, &[(1, 3), (2, 3)]);
+
+        assert_eq!(test.playoff_contenders(), &[1, 2]);
+        assert_eq!(test.standings()[1].position(), 1);
+        assert!(test.playoff_hole(&[(1, 3)]).is_err());
+        assert_eq!(test.playoff_hole(&[(1, 3), (2, 3), (3, 1)]), Err("Player 3 is not in the playoff".to_string()));
+        assert_eq!(test.playoff_hole(&[(1, 3), (2, 4), (2, 2)]), Err("Player 2 played more than once".to_string()));
+        assert_eq!(test.playoff_contenders(), &[1, 2]);
+        assert_eq!(test.playoff_hole(&[(1, 3), (2, 3)]), Ok(None));
+        assert_eq!(test.playoff_hole(&[(1, 3), (2, 2)]), Ok(Some(2)));
+
+        let standings = test.standings();
+
+        assert_eq!(standings[0].player_id(), 2);
+        assert_eq!(standings[1].position(), 2);
+    }
+
+    #[test]
+    fn cards_must_match_the_course() {
+        let mut test = Tournament::new("Open".to_string(), test_course(), 1, None).unwrap();
+        test.register(Player::new(1, None)).unwrap();
+
+        let other = Course::new("Other".to_string(), vec![Hole::new("Danger".to_string(), 3, 1)]).unwrap();
+
+        assert_eq!(
+            test.submit(1, ScoreCard::from_strokes("testMan".to_string(), &other, vec![3])),
+            Err("Card is for Other, not Test".to_string())
+        );
+        assert_eq!(
+            test.submit(1, ScoreCard::new("testMan".to_string(), "Test".to_string(), 3)),
+            Err("Card has 0 holes, Test has 1".to_string())
+        );
+        assert!(test.submit(1, card(3)).is_ok());
+    }
+}
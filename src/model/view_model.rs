@@ -1,3 +1,17 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
 pub trait BaseSubjectType {}
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -5,303 +19,1918 @@ pub enum ActivityState {
     Inactive,
     Loading,
     Active,
+    // Loading or running failed; the model has to be reloaded or reset
+    Error,
+    // Torn down for good, nothing can leave this state
+    Disposed,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct InnerModel<T> {
-    State: ActivityState,
-    Subject: Option<T>,
-}
+impl ActivityState {
+    // The states that can be entered from this one
+    pub fn transitions(&self) -> &'static [ActivityState] {
+        use self::ActivityState::*;
 
-impl<T> InnerModel<T> {
-    pub fn new() -> InnerModel<T> {
-        InnerModel {
-          State: ActivityState::Inactive,
-          Subject: None,
+        match *self {
+            Inactive => &[Loading, Active, Disposed],
+            Loading => &[Active, Inactive, Error, Disposed],
+            Active => &[Inactive, Loading, Error, Disposed],
+            Error => &[Loading, Inactive, Disposed],
+            Disposed => &[],
         }
     }
 
-    fn inner_activate(&mut self) {
-        // Implement this
-        unimplemented!();
+    // Staying in the same state is always allowed, and does nothing
+    pub fn can_transition(&self, to: ActivityState) -> bool {
+        *self == to || self.transitions().contains(&to)
     }
+}
 
-    fn inner_deactivate(&mut self) {
-        unimplemented!();
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TransitionError {
+    _from: ActivityState,
+    _to: ActivityState,
+}
+
+impl TransitionError {
+    pub fn from(&self) -> ActivityState {
+        self._from
     }
 
-    fn inner_loading(&mut self) {
-        unimplemented!();
+    pub fn to(&self) -> ActivityState {
+        self._to
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-pub struct ViewModel<T> {
-    State: ActivityState,
-    Subject: Option<T>,
-    Inner: Option<InnerModel<T>>,
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cannot move a model from {:?} to {:?}", self._from, self._to)
+    }
 }
 
-impl<T> ViewModel<T> {
-    pub fn new(inner_model: Option<InnerModel<T>>) -> ViewModel<T> {
-        ViewModel {
-            Subject: None,
-            State: ActivityState::Inactive,
-            Inner: inner_model,
+impl Error for TransitionError {}
+
+fn check_transition(from: ActivityState, to: ActivityState) -> Result<(), TransitionError> {
+    if from.can_transition(to) {
+        Ok(())
+    } else {
+        Err(TransitionError { _from: from, _to: to })
+    }
+}
+
+// Called with the state being left and the state being entered
+pub type TransitionHook = Rc<dyn Fn(ActivityState, ActivityState)>;
+
+// Callbacks run when a model enters a given state. Hooks don't take part in
+// comparisons between models.
+#[derive(Clone, Default)]
+pub struct TransitionHooks {
+    _hooks: Vec<(ActivityState, TransitionHook)>,
+}
+
+impl TransitionHooks {
+    pub fn register(&mut self, entering: ActivityState, hook: TransitionHook) {
+        self._hooks.push((entering, hook));
+    }
+
+    pub fn len(&self) -> usize {
+        self._hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self._hooks.is_empty()
+    }
+
+    fn run(&self, from: ActivityState, to: ActivityState) {
+        for (entering, hook) in &self._hooks {
+            if *entering == to {
+                hook(from, to);
+            }
         }
     }
 }
 
-pub trait IModel {
-    type SubjectType;
-    fn change_model(&mut self, new_model: Option<InnerModel<Self::SubjectType>>);
-    fn change_subject(&mut self, subject: Option<Self::SubjectType>);
-    fn activate(&mut self);
-    fn deactivate(&mut self);
-    fn loading(&mut self);
+impl fmt::Debug for TransitionHooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TransitionHooks({})", self._hooks.len())
+    }
 }
 
-pub trait StateTransition {
-    fn activate(&mut self);
-    fn deactivate(&mut self);
-    fn loading(&mut self);
+impl PartialEq for TransitionHooks {
+    fn eq(&self, _other: &TransitionHooks) -> bool {
+        true
+    }
 }
 
+// Moves an already checked state along, running the hooks if it actually
+// changed. Returns whether it did.
+fn transition(state: &mut ActivityState, hooks: &TransitionHooks, to: ActivityState) -> bool {
+    if *state == to {
+        return false;
+    }
 
-impl<T: PartialEq> IModel for ViewModel<T> {
-    type SubjectType = T;
+    let from = *state;
+    *state = to;
+    hooks.run(from, to);
+
+    true
+}
 
-    fn change_model(&mut self, new_model: Option<InnerModel<Self::SubjectType>>) {
-        if self.Subject.is_none() {return;}
+// What changed on a view model, for anything that needs to redraw
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ModelEvent {
+    SubjectChanged,
+    ModelChanged,
+    // The state left and the state entered
+    StateChanged(ActivityState, ActivityState),
+    // Bubbled up from the named child model
+    Child(String, Box<ModelEvent>),
+}
 
-        if self.Inner != new_model || self.State == ActivityState::Loading {
-            let previous_state = self.State.clone();
+pub type ModelCallback = Rc<dyn Fn(&ModelEvent)>;
 
-            // Temporarily disable the models changes
-            match self.State {
-                ActivityState::Active | ActivityState::Inactive => {
-                    self.loading();
-                }
-                _ => {}
-            }
+#[derive(Default)]
+struct SubscriberList {
+    _callbacks: Vec<ModelCallback>,
+    _senders: Vec<Sender<ModelEvent>>,
+}
 
-            self.Inner = new_model;
+// Everyone listening to a view model, either by callback or over a channel.
+// Channels whose receiver has been dropped are forgotten on the next event.
+#[derive(Default)]
+pub struct Subscribers {
+    _list: Rc<RefCell<SubscriberList>>,
+}
 
-            // re-set the models changes
-            match previous_state {
-                ActivityState::Active => {
-                    self.activate();
-                }
-                ActivityState::Inactive=> {
-                    self.deactivate();
-                }
-                _ => {}
-            }
-        }
+// A cloned view model starts without listeners, like it starts without
+// children or a pending load
+impl Clone for Subscribers {
+    fn clone(&self) -> Subscribers {
+        Subscribers::default()
+    }
+}
+
+impl Subscribers {
+    // A second handle on the same listeners, which is how child models reach
+    // their parent
+    fn share(&self) -> Subscribers {
+        Subscribers { _list: self._list.clone() }
     }
 
-    fn change_subject(&mut self, subject: Option<Self::SubjectType>) {
-        if self.Subject != subject || self.State == ActivityState::Loading {
-            let was_active = self.State.clone();
+    pub fn add_callback(&mut self, callback: ModelCallback) {
+        self._list.borrow_mut()._callbacks.push(callback);
+    }
 
-            // Temporarily disable the models changes
-            match self.State {
-                ActivityState::Active => {
-                    self.deactivate();
-                    match self.State {
-                        ActivityState::Inactive => {
-                            self.loading();
-                        }
-                        _ => {
-                            print!("Deactivating failed for:");
-                        }
-                    }
-                }
-                _ => {}
-            }
+    pub fn add_channel(&mut self) -> Receiver<ModelEvent> {
+        let (sender, receiver) = channel();
+        self._list.borrow_mut()._senders.push(sender);
+        receiver
+    }
 
-            self.Subject = subject;
+    pub fn len(&self) -> usize {
+        let list = self._list.borrow();
+        list._callbacks.len() + list._senders.len()
+    }
 
-            // re-enable the models changes
-            match was_active {
-                ActivityState::Active | ActivityState::Loading => {
-                    self.activate();
-                }
-                _ => {}
-            }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn notify(&self, event: ModelEvent) {
+        // Callbacks may well cause more events, so don't hold the borrow
+        let callbacks = self._list.borrow()._callbacks.clone();
+
+        for callback in &callbacks {
+            callback(&event);
         }
+
+        self._list.borrow_mut()._senders.retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
+
+impl fmt::Debug for Subscribers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Subscribers({})", self.len())
+    }
+}
+
+// The lifecycle side of a view model, without its subject type, so models
+// over different subjects can sit together in one tree
+pub trait ChildModel {
+    fn state(&self) -> ActivityState;
+    // Whether this model, and everything under it, can make the move
+    fn check_enter(&self, to: ActivityState) -> Result<(), TransitionError>;
+    fn enter(&mut self, to: ActivityState) -> Result<(), TransitionError>;
+    fn listen(&mut self, callback: ModelCallback);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+struct ChildEntry {
+    _name: String,
+    _model: Box<dyn ChildModel>,
+    // Cleared when the child is removed, so it stops bubbling events up
+    _attached: Rc<Cell<bool>>,
+}
+
+// Named child models in the order they were added. Clones of a view model
+// don't take its children.
+#[derive(Default)]
+pub struct Children {
+    _entries: Vec<ChildEntry>,
+}
+
+impl Children {
+    pub fn names(&self) -> Vec<&str> {
+        self._entries.iter().map(|entry| entry._name.as_str()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self._entries.len()
     }
 
-    fn activate(&mut self) {
-        // Idea - if its inactive, auto mark it as loading, then active?
-        // match self.State {
-        //     ActivityState::Inactive => {
-        //       // Mark the Model as Active
-        //       self.State = ActivityState::Loading;
+    pub fn is_empty(&self) -> bool {
+        self._entries.is_empty()
+    }
+}
+
+impl Clone for Children {
+    fn clone(&self) -> Children {
+        Children::default()
+    }
+}
+
+impl fmt::Debug for Children {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Children({:?})", self.names())
+    }
+}
+
+pub type LoadResult<T> = Result<T, String>;
+
+enum LoadSource<T> {
+    Thread(Receiver<LoadResult<T>>),
+    Future(Pin<Box<dyn Future<Output = LoadResult<T>>>>),
+}
+
+// A subject still on its way in. Dropping it cancels the load: a background
+// thread runs to the end but its result is thrown away. Clones of a view
+// model don't share the load, and start in the state it would fall back to.
+pub struct PendingLoad<T> {
+    _source: Option<LoadSource<T>>,
+    // Where the model was before the load, to go back to if it's cancelled
+    _previous: ActivityState,
+}
+
+// Wakes a thread parked in PendingLoad::wait when its future can make progress
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+// For polling without blocking, where poll_load is called again anyway
+struct NoopWaker;
 
-        //       if self.Inner.is_some()
-        //       {
-        //         let mut inner_model = self.Inner.take().unwrap();
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
 
-        //         inner_model.inner_loading();
+impl<T> PendingLoad<T> {
+    fn none() -> PendingLoad<T> {
+        PendingLoad { _source: None, _previous: ActivityState::Inactive }
+    }
 
-        //         self.Inner = Some(inner_model);
-        //       }
+    fn new(source: LoadSource<T>, previous: ActivityState) -> PendingLoad<T> {
+        // Loading with no load of our own to go back to, e.g. part way through
+        // a swap, has nothing better to fall back to
+        let previous = match previous {
+            ActivityState::Loading => ActivityState::Inactive,
+            state => state,
+        };
 
-        //       self.activate();
-        //     }
-        //     ActivityState::Loading => {
-        //       // Mark the Model as Active
-        //       self.State = ActivityState::Active;
+        PendingLoad { _source: Some(source), _previous: previous }
+    }
 
-        //       if self.Inner.is_some()
-        //       {
-        //         let mut inner_model = self.Inner.take().unwrap();
+    fn is_pending(&self) -> bool {
+        self._source.is_some()
+    }
 
-        //         inner_model.InnerActivate();
+    fn cancel(&mut self) -> bool {
+        self._source.take().is_some()
+    }
 
-        //         self.Inner = Some(inner_model);
-        //       }
-        //     }
-        // }
+    // Checks on the load without blocking, None while it is still running
+    fn poll(&mut self) -> Option<LoadResult<T>> {
+        self.poll_with(&Waker::from(Arc::new(NoopWaker)))
+    }
 
-        if self.State == ActivityState::Inactive || self.State == ActivityState::Loading {
-            // Mark the Model as Active
-            self.State = ActivityState::Active;
+    fn poll_with(&mut self, waker: &Waker) -> Option<LoadResult<T>> {
+        let result = match self._source {
+            Some(LoadSource::Thread(ref receiver)) => match receiver.try_recv() {
+                Ok(result) => Some(result),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => Some(Err("Loader thread stopped without a result".to_string())),
+            },
+            Some(LoadSource::Future(ref mut future)) => {
+                let mut context = Context::from_waker(waker);
+
+                match future.as_mut().poll(&mut context) {
+                    Poll::Ready(result) => Some(result),
+                    Poll::Pending => None,
+                }
+            }
+            None => None,
+        };
 
-            if self.Inner.is_some() {
+        if result.is_some() {
+            self._source = None;
+        }
 
-                let mut inner_model = self.Inner.take().unwrap();
+        result
+    }
 
-                inner_model.inner_activate();
+    // Blocks until the load finishes. Futures are polled again each time
+    // they wake the thread, which sleeps in between.
+    fn wait(&mut self) -> Option<LoadResult<T>> {
+        if let Some(LoadSource::Thread(ref receiver)) = self._source {
+            let result = receiver.recv().unwrap_or_else(|_| Err("Loader thread stopped without a result".to_string()));
+            self._source = None;
+            return Some(result);
+        }
 
-                self.Inner = Some(inner_model);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
 
+        while self.is_pending() {
+            if let Some(result) = self.poll_with(&waker) {
+                return Some(result);
             }
 
+            thread::park();
         }
+
+        None
     }
+}
+
+impl<T> Clone for PendingLoad<T> {
+    fn clone(&self) -> PendingLoad<T> {
+        PendingLoad::none()
+    }
+}
+
+impl<T> fmt::Debug for PendingLoad<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PendingLoad({})", self.is_pending())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LoadStatus {
+    // Nothing is being loaded
+    Idle,
+    Pending,
+    // The subject arrived and the model is now Active
+    Loaded,
+    // The loader failed and the model is now in Error
+    Failed(String),
+}
 
-    fn deactivate(&mut self) {
-        if self.State == ActivityState::Active || self.State == ActivityState::Loading {
+// A value replaced on a view model, kept so the change can be reversed
+#[derive(Clone, Debug)]
+enum Change<T> {
+    Subject(Option<T>),
+    Model(Option<InnerModel<T>>),
+}
 
-            // Mark the Model as Inactive
-            self.State = ActivityState::Inactive;
+impl<T> Change<T> {
+    fn kind(&self) -> HistoryKind {
+        match *self {
+            Change::Subject(_) => HistoryKind::Subject,
+            Change::Model(_) => HistoryKind::Model,
+        }
+    }
+}
 
-            let mut inner_model = self.Inner.take().unwrap();
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HistoryKind {
+    Subject,
+    Model,
+}
 
-            inner_model.inner_deactivate();
+// How many changes are kept for undo until set_history_limit says otherwise
+pub const DEFAULT_HISTORY_LIMIT: usize = 50;
 
-            self.Inner = Some(inner_model);
+// Bounded undo/redo stacks; the oldest changes are dropped past the limit,
+// and a limit of 0 turns history off
+#[derive(Clone, Debug)]
+pub struct History<T> {
+    _undo: VecDeque<Change<T>>,
+    _redo: Vec<Change<T>>,
+    _limit: usize,
+}
 
+impl<T> History<T> {
+    fn new() -> History<T> {
+        History {
+            _undo: VecDeque::new(),
+            _redo: Vec::new(),
+            _limit: DEFAULT_HISTORY_LIMIT,
         }
     }
 
-    fn loading(&mut self) {
-        if self.State == ActivityState::Active || self.State == ActivityState::Inactive {
+    pub fn limit(&self) -> usize {
+        self._limit
+    }
+
+    // Changes that can be undone, most recent first
+    pub fn undo_entries(&self) -> Vec<HistoryKind> {
+        self._undo.iter().rev().map(|change| change.kind()).collect()
+    }
 
-            // Mark the Model as Inactive
-            self.State = ActivityState::Loading;
+    // Changes that can be redone, next to be redone first
+    pub fn redo_entries(&self) -> Vec<HistoryKind> {
+        self._redo.iter().rev().map(|change| change.kind()).collect()
+    }
 
-            let mut inner_model = self.Inner.take().unwrap();
+    fn set_limit(&mut self, limit: usize) {
+        self._limit = limit;
+        self.trim();
+    }
 
-            inner_model.inner_loading();
+    fn record(&mut self, change: Change<T>) {
+        if self._limit == 0 {
+            return;
+        }
 
-            self.Inner = Some(inner_model);
+        self._undo.push_back(change);
+        self._redo.clear();
+        self.trim();
+    }
 
+    fn trim(&mut self) {
+        while self._undo.len() > self._limit {
+            self._undo.pop_front();
         }
+
+        self._redo.truncate(self._limit);
     }
 }
 
-impl<T: PartialEq> StateTransition for InnerModel<T> {
-    fn activate(&mut self) {
-        self.inner_activate();
+#[derive(Clone, Debug, PartialEq)]
+pub struct InnerModel<T> {
+    State: ActivityState,
+    Subject: Option<T>,
+    Hooks: TransitionHooks,
+}
+
+impl<T> Default for InnerModel<T> {
+    fn default() -> InnerModel<T> {
+        InnerModel::new()
+    }
+}
+
+impl<T> InnerModel<T> {
+    pub fn new() -> InnerModel<T> {
+        InnerModel {
+          State: ActivityState::Inactive,
+          Subject: None,
+          Hooks: TransitionHooks::default(),
+        }
+    }
+
+    pub fn state(&self) -> ActivityState {
+        self.State
     }
 
-    fn deactivate(&mut self) {
-        self.inner_deactivate();
+    pub fn on_transition(&mut self, entering: ActivityState, hook: TransitionHook) {
+        self.Hooks.register(entering, hook);
     }
 
-    fn loading(&mut self) {
-        self.inner_loading();
+    fn inner_transition(&mut self, to: ActivityState) -> Result<(), TransitionError> {
+        check_transition(self.State, to)?;
+        transition(&mut self.State, &self.Hooks, to);
+
+        Ok(())
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{ActivityState, IModel, InnerModel, ViewModel};
-    use model::map::{Map, MapBuilder};
+#[derive(Debug)]
+pub struct ViewModel<T> {
+    State: ActivityState,
+    Subject: Option<T>,
+    Inner: Option<InnerModel<T>>,
+    Hooks: TransitionHooks,
+    Subscribers: Subscribers,
+    Pending: PendingLoad<T>,
+    History: History<T>,
+    Children: Children,
+}
 
-    #[test]
-    fn no_subject() {
-        let elem: ViewModel<Map> = ViewModel::new(None);
+impl<T: Clone> Clone for ViewModel<T> {
+    // A copy made during a load doesn't get the load, so it starts where the
+    // load would fall back to rather than waiting in Loading for nothing
+    fn clone(&self) -> ViewModel<T> {
+        let mut copy = ViewModel {
+            State: self.State,
+            Subject: self.Subject.clone(),
+            Inner: self.Inner.clone(),
+            Hooks: self.Hooks.clone(),
+            Subscribers: self.Subscribers.clone(),
+            Pending: self.Pending.clone(),
+            History: self.History.clone(),
+            Children: self.Children.clone(),
+        };
 
-        assert!({
-            match elem.Subject {
-                None => true,
-                _ => false,
+        if self.Pending.is_pending() {
+            copy.State = self.Pending._previous;
+
+            if let Some(ref mut inner_model) = copy.Inner {
+                inner_model.State = self.Pending._previous;
             }
-        });
+        }
+
+        copy
     }
+}
 
-    #[test]
-    fn subject_not_changing() {
-        let mut elem: ViewModel<Map> = ViewModel::new(None);
+impl<T> ViewModel<T> {
+    pub fn new(inner_model: Option<InnerModel<T>>) -> ViewModel<T> {
+        ViewModel {
+            Subject: None,
+            State: ActivityState::Inactive,
+            Inner: inner_model,
+            Hooks: TransitionHooks::default(),
+            Subscribers: Subscribers::default(),
+            Pending: PendingLoad::none(),
+            History: History::new(),
+            Children: Children::default(),
+        }
+    }
 
-        elem.change_subject(None);
+    pub fn state(&self) -> ActivityState {
+        self.State
+    }
 
-        assert!({
-            match elem.Subject {
-                None => true,
-                _ => false,
+    pub fn subject(&self) -> Option<&T> {
+        self.Subject.as_ref()
+    }
+
+    pub fn inner(&self) -> Option<&InnerModel<T>> {
+        self.Inner.as_ref()
+    }
+
+    pub fn on_transition(&mut self, entering: ActivityState, hook: TransitionHook) {
+        self.Hooks.register(entering, hook);
+    }
+
+    pub fn children(&self) -> &Children {
+        &self.Children
+    }
+
+    pub fn child(&self, name: &str) -> Option<&dyn ChildModel> {
+        self.Children._entries
+            .iter()
+            .find(|entry| entry._name == name)
+            .map(|entry| &*entry._model)
+    }
+
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut dyn ChildModel> {
+        match self.Children._entries.iter_mut().find(|entry| entry._name == name) {
+            Some(entry) => Some(&mut *entry._model),
+            None => None,
+        }
+    }
+
+    // The named child as its concrete type, e.g. a ViewModel<Map>
+    pub fn child_as<M: 'static>(&self, name: &str) -> Option<&M> {
+        self.child(name).and_then(|child| child.as_any().downcast_ref::<M>())
+    }
+
+    pub fn child_as_mut<M: 'static>(&mut self, name: &str) -> Option<&mut M> {
+        self.child_mut(name).and_then(|child| child.as_any_mut().downcast_mut::<M>())
+    }
+
+    // Adds a child, first bringing it into this model's state. Its events
+    // bubble up as ModelEvent::Child. A child with the same name is replaced
+    // and handed back.
+    pub fn add_child<M: ChildModel + 'static>(&mut self, name: &str, mut child: M) -> Result<Option<Box<dyn ChildModel>>, TransitionError> {
+        child.check_enter(self.State)?;
+        child.enter(self.State)?;
+
+        let attached = Rc::new(Cell::new(true));
+        let link = attached.clone();
+        let parent = self.Subscribers.share();
+        let child_name = name.to_string();
+
+        child.listen(Rc::new(move |event| {
+            if link.get() {
+                parent.notify(ModelEvent::Child(child_name.clone(), Box::new(event.clone())));
             }
+        }));
+
+        let previous = self.remove_child(name);
+
+        self.Children._entries.push(ChildEntry {
+            _name: name.to_string(),
+            _model: Box::new(child),
+            _attached: attached,
         });
 
-        match MapBuilder::from_named(None) {
-            Ok(map) => elem.change_subject(Some(map)),
-            Err(ex) => panic!(ex),
-        };
+        Ok(previous)
+    }
 
-        assert!({
-            let name_test = match elem.clone().Subject {
-                None => false,
-                Some(map) => map.name() == "Tutorial",
-            };
+    // Detaches a child; its state is left as it was
+    pub fn remove_child(&mut self, name: &str) -> Option<Box<dyn ChildModel>> {
+        let idx = self.Children._entries.iter().position(|entry| entry._name == name)?;
+        let entry = self.Children._entries.remove(idx);
 
-            let subject_test = match elem.State {
-                ActivityState::Inactive => true,
-                _ => false,
-            };
+        entry._attached.set(false);
 
-            name_test && subject_test
-        });
+        Some(entry._model)
     }
 
-    #[test]
-    fn subject_activating() {
-        use self::ActivityState::*;
+    fn check_tree(&self, to: ActivityState) -> Result<(), TransitionError> {
+        check_transition(self.State, to)?;
 
-        let mut elem: ViewModel<Map> = ViewModel::new(None);
+        if let Some(ref inner_model) = self.Inner {
+            check_transition(inner_model.State, to)?;
+        }
 
-        // Fails, need to implement Activate...
-        elem.activate();
+        for entry in &self.Children._entries {
+            entry._model.check_enter(to)?;
+        }
 
-        assert!(match elem.State {
-            Active => true,
-            _ => false,
-        });
+        Ok(())
     }
 
-    #[test]
-    fn change_model_no_subject() {
-        let mut elem: ViewModel<Map> = ViewModel::new(None);
+    // Moves this model, its inner model and its children together. The whole
+    // tree is checked against the transition table first, so nothing moves if
+    // any of it can't. Children follow their parent into Loading and Active,
+    // in the order they were added, and leave before it for anything else, in
+    // reverse order.
+    fn transition_to(&mut self, to: ActivityState) -> Result<(), TransitionError> {
+        self.check_tree(to)?;
 
-        elem.change_model(Some(InnerModel::new()));
+        let parent_first = to == ActivityState::Loading || to == ActivityState::Active;
 
-        // Model shouldn't be set, as there is no subject
-        assert!(match elem.Inner {
-            None => true,
-            Some(_) => false,
-        });
+        if !parent_first {
+            for entry in self.Children._entries.iter_mut().rev() {
+                entry._model.enter(to)?;
+            }
+        }
+
+        let from = self.State;
+
+        if transition(&mut self.State, &self.Hooks, to) {
+            self.Subscribers.notify(ModelEvent::StateChanged(from, to));
+        }
+
+        if let Some(ref mut inner_model) = self.Inner {
+            inner_model.inner_transition(to)?;
+        }
+
+        if parent_first {
+            for entry in self.Children._entries.iter_mut() {
+                entry._model.enter(to)?;
+            }
+        }
+
+        Ok(())
     }
-}
\ No newline at end of file
+}
+
+pub trait IModel {
+    type SubjectType;
+    fn change_model(&mut self, new_model: Option<InnerModel<Self::SubjectType>>) -> Result<(), TransitionError>;
+    fn change_subject(&mut self, subject: Option<Self::SubjectType>) -> Result<(), TransitionError>;
+    fn activate(&mut self) -> Result<(), TransitionError>;
+    fn deactivate(&mut self) -> Result<(), TransitionError>;
+    fn loading(&mut self) -> Result<(), TransitionError>;
+    fn fail(&mut self) -> Result<(), TransitionError>;
+    fn dispose(&mut self) -> Result<(), TransitionError>;
+    fn subscribe(&mut self) -> Receiver<ModelEvent>;
+    fn on_change(&mut self, callback: ModelCallback);
+}
+
+pub trait StateTransition {
+    fn activate(&mut self) -> Result<(), TransitionError>;
+    fn deactivate(&mut self) -> Result<(), TransitionError>;
+    fn loading(&mut self) -> Result<(), TransitionError>;
+    fn fail(&mut self) -> Result<(), TransitionError>;
+    fn dispose(&mut self) -> Result<(), TransitionError>;
+}
+
+
+impl<T: PartialEq> IModel for ViewModel<T> {
+    type SubjectType = T;
+
+    fn change_model(&mut self, new_model: Option<InnerModel<Self::SubjectType>>) -> Result<(), TransitionError> {
+        if let Some(previous) = self.swap_model(new_model)? {
+            self.History.record(Change::Model(previous));
+        }
+
+        Ok(())
+    }
+
+    fn change_subject(&mut self, subject: Option<Self::SubjectType>) -> Result<(), TransitionError> {
+        if let Some(previous) = self.swap_subject(subject)? {
+            self.History.record(Change::Subject(previous));
+        }
+
+        Ok(())
+    }
+
+    fn activate(&mut self) -> Result<(), TransitionError> {
+        self.transition_to(ActivityState::Active)
+    }
+
+    fn deactivate(&mut self) -> Result<(), TransitionError> {
+        self.transition_to(ActivityState::Inactive)
+    }
+
+    fn loading(&mut self) -> Result<(), TransitionError> {
+        self.transition_to(ActivityState::Loading)
+    }
+
+    fn fail(&mut self) -> Result<(), TransitionError> {
+        self.transition_to(ActivityState::Error)
+    }
+
+    fn dispose(&mut self) -> Result<(), TransitionError> {
+        self.transition_to(ActivityState::Disposed)
+    }
+
+    fn subscribe(&mut self) -> Receiver<ModelEvent> {
+        self.Subscribers.add_channel()
+    }
+
+    fn on_change(&mut self, callback: ModelCallback) {
+        self.Subscribers.add_callback(callback);
+    }
+}
+
+impl<T: PartialEq> ViewModel<T> {
+    // Replaces the inner model through the lifecycle, handing back the old one
+    // if anything changed
+    fn swap_model(&mut self, new_model: Option<InnerModel<T>>) -> Result<Option<Option<InnerModel<T>>>, TransitionError> {
+        if self.Subject.is_none() {return Ok(None);}
+
+        // Swapping models passes through Loading, for the new model as well as
+        // the tree it joins; check them all before anything moves
+        self.check_tree(ActivityState::Loading)?;
+
+        if let Some(ref model) = new_model {
+            check_transition(model.State, ActivityState::Loading)?;
+        }
+
+        if self.model_differs(&new_model) {
+            let previous_state = self.State;
+
+            // Temporarily disable the models changes
+            self.loading()?;
+
+            let previous = mem::replace(&mut self.Inner, new_model);
+            self.Subscribers.notify(ModelEvent::ModelChanged);
+
+            if let Some(ref mut inner_model) = self.Inner {
+                inner_model.inner_transition(ActivityState::Loading)?;
+            }
+
+            // re-set the models changes
+            match previous_state {
+                ActivityState::Active => self.activate()?,
+                ActivityState::Loading => {}
+                _ => self.deactivate()?,
+            }
+
+            return Ok(Some(previous));
+        }
+
+        Ok(None)
+    }
+
+    fn model_differs(&self, model: &Option<InnerModel<T>>) -> bool {
+        self.Subject.is_some() && (self.Inner != *model || self.State == ActivityState::Loading)
+    }
+
+    fn subject_differs(&self, subject: &Option<T>) -> bool {
+        self.Subject != *subject || self.State == ActivityState::Loading
+    }
+
+    // Replaces the subject through the lifecycle, handing back the old one if
+    // anything changed
+    fn swap_subject(&mut self, subject: Option<T>) -> Result<Option<Option<T>>, TransitionError> {
+        // Swapping subjects passes through Loading
+        check_transition(self.State, ActivityState::Loading)?;
+
+        // A subject set by hand wins over one still loading
+        self.Pending.cancel();
+
+        if self.subject_differs(&subject) {
+            let was_active = self.State;
+
+            // Temporarily disable the models changes
+            if self.State == ActivityState::Active {
+                self.deactivate()?;
+                self.loading()?;
+            }
+
+            let previous = mem::replace(&mut self.Subject, subject);
+            self.Subscribers.notify(ModelEvent::SubjectChanged);
+
+            // re-enable the models changes
+            match was_active {
+                ActivityState::Active | ActivityState::Loading => self.activate()?,
+                _ => {}
+            }
+
+            return Ok(Some(previous));
+        }
+
+        Ok(None)
+    }
+
+    // Keep up to limit subject and inner model changes for undo; 0 turns
+    // history off and forgets what was kept
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.History.set_limit(limit);
+    }
+
+    pub fn history(&self) -> &History<T> {
+        &self.History
+    }
+
+    // Whether undo would change anything
+    pub fn can_undo(&self) -> bool {
+        self.History._undo.iter().any(|change| self.changes_anything(change))
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.History._redo.iter().any(|change| self.changes_anything(change))
+    }
+
+    // Puts back the last subject or inner model replaced, going through the
+    // same deactivate/reload/reactivate steps as the original change. Entries
+    // that would change nothing are dropped on the way, so this returns
+    // whether anything was undone.
+    pub fn undo(&mut self) -> Result<bool, TransitionError> {
+        check_transition(self.State, ActivityState::Loading)?;
+
+        while let Some(change) = self.History._undo.pop_back() {
+            if !self.changes_anything(&change) {
+                continue;
+            }
+
+            if let Some(reverse) = self.apply_change(change)? {
+                self.History._redo.push(reverse);
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    pub fn redo(&mut self) -> Result<bool, TransitionError> {
+        check_transition(self.State, ActivityState::Loading)?;
+
+        while let Some(change) = self.History._redo.pop() {
+            if !self.changes_anything(&change) {
+                continue;
+            }
+
+            if let Some(reverse) = self.apply_change(change)? {
+                self.History._undo.push_back(reverse);
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn changes_anything(&self, change: &Change<T>) -> bool {
+        match *change {
+            Change::Subject(ref subject) => self.subject_differs(subject),
+            Change::Model(ref model) => self.model_differs(model),
+        }
+    }
+
+    fn apply_change(&mut self, change: Change<T>) -> Result<Option<Change<T>>, TransitionError> {
+        match change {
+            Change::Subject(subject) => Ok(self.swap_subject(subject)?.map(Change::Subject)),
+            Change::Model(model) => Ok(self.swap_model(model)?.map(Change::Model)),
+        }
+    }
+}
+
+impl<T: PartialEq + 'static> ViewModel<T> {
+    // Loads the subject on a background thread, e.g. parsing a large map from
+    // disk. The model sits in Loading until poll_load sees the result.
+    pub fn load_in_background<F>(&mut self, loader: F) -> Result<(), TransitionError>
+        where F: FnOnce() -> LoadResult<T> + Send + 'static, T: Send
+    {
+        let previous = self.fallback_state();
+
+        self.loading()?;
+
+        let (sender, receiver) = channel();
+
+        thread::spawn(move || {
+            // Nobody to tell if the load was cancelled
+            let _ = sender.send(loader());
+        });
+
+        self.Pending = PendingLoad::new(LoadSource::Thread(receiver), previous);
+
+        Ok(())
+    }
+
+    // As load_in_background, with the subject coming from a future that is
+    // polled each time poll_load is called
+    pub fn load_future<F>(&mut self, future: F) -> Result<(), TransitionError>
+        where F: Future<Output = LoadResult<T>> + 'static
+    {
+        let previous = self.fallback_state();
+
+        self.loading()?;
+        self.Pending = PendingLoad::new(LoadSource::Future(Box::pin(future)), previous);
+
+        Ok(())
+    }
+
+    // Where a new load goes back to if cancelled. One that replaces a load
+    // still pending goes back to where the first one started.
+    fn fallback_state(&self) -> ActivityState {
+        if self.Pending.is_pending() {
+            self.Pending._previous
+        } else {
+            self.State
+        }
+    }
+
+    pub fn is_loading_subject(&self) -> bool {
+        self.Pending.is_pending()
+    }
+
+    // Drops a pending load and puts the model back in the state it was in
+    // before the load started. Returns whether there was a load to cancel.
+    pub fn cancel_load(&mut self) -> Result<bool, TransitionError> {
+        let previous = self.Pending._previous;
+
+        if !self.Pending.cancel() {
+            return Ok(false);
+        }
+
+        self.transition_to(previous)?;
+
+        Ok(true)
+    }
+
+    // Checks on a pending load without blocking, finishing it if it's done
+    pub fn poll_load(&mut self) -> Result<LoadStatus, TransitionError> {
+        if !self.Pending.is_pending() {
+            return Ok(LoadStatus::Idle);
+        }
+
+        match self.Pending.poll() {
+            Some(result) => self.finish_load(result),
+            None => Ok(LoadStatus::Pending),
+        }
+    }
+
+    // Blocks until a pending load finishes
+    pub fn wait_for_load(&mut self) -> Result<LoadStatus, TransitionError> {
+        match self.Pending.wait() {
+            Some(result) => self.finish_load(result),
+            None => Ok(LoadStatus::Idle),
+        }
+    }
+
+    fn finish_load(&mut self, result: LoadResult<T>) -> Result<LoadStatus, TransitionError> {
+        match result {
+            Ok(subject) => {
+                let subject = Some(subject);
+
+                // Undone like a subject set by hand
+                if self.Subject != subject {
+                    let previous = mem::replace(&mut self.Subject, subject);
+                    self.History.record(Change::Subject(previous));
+                }
+
+                self.Subscribers.notify(ModelEvent::SubjectChanged);
+                self.activate()?;
+
+                Ok(LoadStatus::Loaded)
+            }
+            Err(message) => {
+                self.fail()?;
+
+                Ok(LoadStatus::Failed(message))
+            }
+        }
+    }
+}
+
+impl<T: PartialEq + 'static> ChildModel for ViewModel<T> {
+    fn state(&self) -> ActivityState {
+        self.State
+    }
+
+    fn check_enter(&self, to: ActivityState) -> Result<(), TransitionError> {
+        self.check_tree(to)
+    }
+
+    fn enter(&mut self, to: ActivityState) -> Result<(), TransitionError> {
+        self.transition_to(to)
+    }
+
+    fn listen(&mut self, callback: ModelCallback) {
+        self.Subscribers.add_callback(callback);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<T: PartialEq> StateTransition for InnerModel<T> {
+    fn activate(&mut self) -> Result<(), TransitionError> {
+        self.inner_transition(ActivityState::Active)
+    }
+
+    fn deactivate(&mut self) -> Result<(), TransitionError> {
+        self.inner_transition(ActivityState::Inactive)
+    }
+
+    fn loading(&mut self) -> Result<(), TransitionError> {
+        self.inner_transition(ActivityState::Loading)
+    }
+
+    fn fail(&mut self) -> Result<(), TransitionError> {
+        self.inner_transition(ActivityState::Error)
+    }
+
+    fn dispose(&mut self) -> Result<(), TransitionError> {
+        self.inner_transition(ActivityState::Disposed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use std::future::{self, Future};
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::channel;
+    use std::task::{Context, Poll};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{ActivityState, Change, HistoryKind, DEFAULT_HISTORY_LIMIT, IModel, InnerModel, LoadResult, LoadStatus, ModelEvent, StateTransition, ViewModel};
+    use super::ActivityState::{Active, Disposed, Error, Inactive, Loading};
+    use model::map::{Map, MapBuilder, MapName};
+
+    fn tutorial() -> Map {
+        MapBuilder::from_named(None).unwrap()
+    }
+
+    fn with_inner() -> ViewModel<Map> {
+        ViewModel::new(Some(InnerModel::new()))
+    }
+
+    fn inner_state(elem: &ViewModel<Map>) -> ActivityState {
+        elem.inner().unwrap().state()
+    }
+
+    #[test]
+    fn no_subject() {
+        let elem: ViewModel<Map> = ViewModel::new(None);
+
+        assert!({
+            elem.Subject.is_none()
+        });
+    }
+
+    #[test]
+    fn subject_not_changing() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.change_subject(None).unwrap();
+
+        assert!({
+            elem.Subject.is_none()
+        });
+
+        match MapBuilder::from_named(None) {
+            Ok(map) => elem.change_subject(Some(map)).unwrap(),
+            Err(ex) => panic!("{}", ex),
+        };
+
+        assert!({
+            let name_test = match elem.clone().Subject {
+                None => false,
+                Some(map) => map.name() == "Tutorial",
+            };
+
+            let subject_test = elem.State == ActivityState::Inactive;
+
+            name_test && subject_test
+        });
+    }
+
+    #[test]
+    fn subject_activating() {
+        use self::ActivityState::*;
+
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        // Fails, need to implement Activate...
+        elem.activate().unwrap();
+
+        assert!(elem.State == Active);
+    }
+
+    #[test]
+    fn change_model_no_subject() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.change_model(Some(InnerModel::new())).unwrap();
+
+        // Model shouldn't be set, as there is no subject
+        assert!(elem.Inner.is_none());
+    }
+
+    #[test]
+    fn inactive_to_active() {
+        let mut elem = with_inner();
+
+        elem.activate().unwrap();
+
+        assert_eq!(elem.state(), ActivityState::Active);
+        assert_eq!(inner_state(&elem), ActivityState::Active);
+    }
+
+    #[test]
+    fn loading_to_active() {
+        let mut elem = with_inner();
+
+        elem.loading().unwrap();
+        elem.activate().unwrap();
+
+        assert_eq!(elem.state(), ActivityState::Active);
+        assert_eq!(inner_state(&elem), ActivityState::Active);
+    }
+
+    #[test]
+    fn active_to_inactive() {
+        let mut elem = with_inner();
+
+        elem.activate().unwrap();
+        elem.deactivate().unwrap();
+
+        assert_eq!(elem.state(), ActivityState::Inactive);
+        assert_eq!(inner_state(&elem), ActivityState::Inactive);
+    }
+
+    #[test]
+    fn loading_to_inactive() {
+        let mut elem = with_inner();
+
+        elem.loading().unwrap();
+        elem.deactivate().unwrap();
+
+        assert_eq!(elem.state(), ActivityState::Inactive);
+        assert_eq!(inner_state(&elem), ActivityState::Inactive);
+    }
+
+    #[test]
+    fn inactive_and_active_to_loading() {
+        let mut elem = with_inner();
+
+        elem.loading().unwrap();
+
+        assert_eq!(elem.state(), ActivityState::Loading);
+        assert_eq!(inner_state(&elem), ActivityState::Loading);
+
+        elem.activate().unwrap();
+        elem.loading().unwrap();
+
+        assert_eq!(elem.state(), ActivityState::Loading);
+        assert_eq!(inner_state(&elem), ActivityState::Loading);
+    }
+
+    #[test]
+    fn no_inner_model_does_not_panic() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.loading().unwrap();
+        elem.deactivate().unwrap();
+        elem.activate().unwrap();
+        elem.loading().unwrap();
+
+        assert_eq!(elem.state(), ActivityState::Loading);
+        assert!(elem.inner().is_none());
+    }
+
+    #[test]
+    fn repeated_transitions_are_ignored() {
+        let mut elem = with_inner();
+        let calls = Rc::new(RefCell::new(0));
+        let counter = calls.clone();
+
+        elem.on_transition(ActivityState::Active, Rc::new(move |_, _| *counter.borrow_mut() += 1));
+        elem.activate().unwrap();
+        elem.activate().unwrap();
+
+        assert_eq!(*calls.borrow(), 1);
+    }
+
+    #[test]
+    fn hooks_run_per_transition() {
+        let mut inner = InnerModel::new();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let inner_seen = seen.clone();
+        let outer_seen = seen.clone();
+
+        inner.on_transition(ActivityState::Loading, Rc::new(move |from, to| inner_seen.borrow_mut().push(("inner", from, to))));
+
+        let mut elem: ViewModel<Map> = ViewModel::new(Some(inner));
+
+        elem.on_transition(ActivityState::Active, Rc::new(move |from, to| outer_seen.borrow_mut().push(("outer", from, to))));
+        elem.loading().unwrap();
+        elem.activate().unwrap();
+        elem.deactivate().unwrap();
+
+        assert_eq!(
+            *seen.borrow(),
+            vec![
+                ("inner", ActivityState::Inactive, ActivityState::Loading),
+                ("outer", ActivityState::Loading, ActivityState::Active),
+            ]
+        );
+    }
+
+    #[test]
+    fn change_subject_while_active() {
+        let mut elem = with_inner();
+
+        elem.activate().unwrap();
+        elem.change_subject(Some(tutorial())).unwrap();
+
+        assert_eq!(elem.state(), ActivityState::Active);
+        assert_eq!(inner_state(&elem), ActivityState::Active);
+        assert_eq!(elem.subject().unwrap().name(), "Tutorial");
+    }
+
+    #[test]
+    fn change_model_while_active() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+        let mut replacement = InnerModel::new();
+        let entered = Rc::new(RefCell::new(false));
+        let flag = entered.clone();
+
+        replacement.on_transition(ActivityState::Active, Rc::new(move |_, _| *flag.borrow_mut() = true));
+
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.activate().unwrap();
+        elem.change_model(Some(replacement)).unwrap();
+
+        assert_eq!(elem.state(), ActivityState::Active);
+        assert_eq!(inner_state(&elem), ActivityState::Active);
+        assert!(*entered.borrow());
+    }
+
+    #[test]
+    fn change_model_while_inactive() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.change_model(Some(InnerModel::new())).unwrap();
+
+        assert_eq!(elem.state(), ActivityState::Inactive);
+        assert_eq!(inner_state(&elem), ActivityState::Inactive);
+
+        elem.change_model(None).unwrap();
+
+        assert!(elem.inner().is_none());
+    }
+
+    #[test]
+    fn disposed_model_is_refused() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+        let mut disposed = InnerModel::new();
+
+        StateTransition::dispose(&mut disposed).unwrap();
+
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.change_model(Some(InnerModel::new())).unwrap();
+        elem.activate().unwrap();
+
+        let entries = elem.history().undo_entries();
+
+        assert_eq!(elem.change_model(Some(disposed)).map_err(|error| error.from()), Err(Disposed));
+        assert_eq!(elem.state(), Active);
+        assert_eq!(inner_state(&elem), Active);
+        assert_eq!(elem.history().undo_entries(), entries);
+        assert_eq!(elem.deactivate(), Ok(()));
+        assert_eq!(elem.undo(), Ok(true));
+        assert!(elem.inner().is_none());
+    }
+
+    #[test]
+    fn inner_state_transition() {
+        let mut inner: InnerModel<Map> = InnerModel::new();
+
+        StateTransition::loading(&mut inner).unwrap();
+        assert_eq!(inner.state(), ActivityState::Loading);
+
+        StateTransition::activate(&mut inner).unwrap();
+        assert_eq!(inner.state(), ActivityState::Active);
+
+        StateTransition::deactivate(&mut inner).unwrap();
+        assert_eq!(inner.state(), ActivityState::Inactive);
+    }
+
+    #[test]
+    fn events_over_channel() {
+        use self::ActivityState::*;
+
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+        let events = elem.subscribe();
+
+        elem.activate().unwrap();
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.change_model(Some(InnerModel::new())).unwrap();
+
+        assert_eq!(
+            events.try_iter().collect::<Vec<ModelEvent>>(),
+            vec![
+                ModelEvent::StateChanged(Inactive, Active),
+                ModelEvent::StateChanged(Active, Inactive),
+                ModelEvent::StateChanged(Inactive, Loading),
+                ModelEvent::SubjectChanged,
+                ModelEvent::StateChanged(Loading, Active),
+                ModelEvent::StateChanged(Active, Loading),
+                ModelEvent::ModelChanged,
+                ModelEvent::StateChanged(Loading, Active),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_to_callbacks() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let recorder = seen.clone();
+
+        elem.on_change(Rc::new(move |event| recorder.borrow_mut().push(event.clone())));
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.change_subject(Some(tutorial())).unwrap();
+
+        assert_eq!(*seen.borrow(), vec![ModelEvent::SubjectChanged]);
+    }
+
+    #[test]
+    fn clones_have_their_own_listeners() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+        let receiver = elem.subscribe();
+        let mut copy = elem.clone();
+
+        copy.activate().unwrap();
+
+        assert!(receiver.try_recv().is_err());
+        assert!(copy.Subscribers.is_empty());
+
+        elem.activate().unwrap();
+
+        assert_eq!(receiver.try_recv(), Ok(ModelEvent::StateChanged(Inactive, Active)));
+    }
+
+    #[test]
+    fn dropped_receivers_are_forgotten() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        drop(elem.subscribe());
+        elem.activate().unwrap();
+
+        assert!(elem.Subscribers.is_empty());
+    }
+
+    #[test]
+    fn transition_table() {
+        let states = [Inactive, Loading, Active, Error, Disposed];
+
+        for from in &states {
+            assert!(from.can_transition(*from));
+        }
+
+        assert!(!Inactive.can_transition(Error));
+        assert!(!Error.can_transition(Active));
+        assert!(states.iter().all(|to| *to == Disposed || !Disposed.can_transition(*to)));
+    }
+
+    #[test]
+    fn failing_and_recovering() {
+        let mut elem = with_inner();
+
+        elem.loading().unwrap();
+        elem.fail().unwrap();
+
+        assert_eq!(elem.state(), Error);
+        assert_eq!(inner_state(&elem), Error);
+
+        let err = elem.activate().unwrap_err();
+
+        assert_eq!((err.from(), err.to()), (Error, Active));
+        assert_eq!(elem.state(), Error);
+
+        elem.loading().unwrap();
+        elem.activate().unwrap();
+
+        assert_eq!(inner_state(&elem), Active);
+    }
+
+    #[test]
+    fn illegal_moves_leave_state_alone() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        assert!(elem.fail().is_err());
+        assert_eq!(elem.state(), Inactive);
+    }
+
+    #[test]
+    fn inner_model_can_block_transition() {
+        let mut inner = InnerModel::new();
+
+        StateTransition::dispose(&mut inner).unwrap();
+
+        let mut elem: ViewModel<Map> = ViewModel::new(Some(inner));
+
+        assert!(elem.activate().is_err());
+        assert_eq!(elem.state(), Inactive);
+    }
+
+    #[test]
+    fn disposed_is_final() {
+        let mut elem = with_inner();
+        let events = elem.subscribe();
+
+        elem.activate().unwrap();
+        elem.dispose().unwrap();
+
+        assert_eq!(inner_state(&elem), Disposed);
+        assert!(elem.activate().is_err());
+        assert!(elem.deactivate().is_err());
+        assert!(elem.change_subject(Some(tutorial())).is_err());
+        assert!(elem.subject().is_none());
+        assert_eq!(events.try_iter().last(), Some(ModelEvent::StateChanged(Active, Disposed)));
+    }
+
+    #[test]
+    fn transition_error_message() {
+        let err = ViewModel::<Map>::new(None).fail().unwrap_err();
+
+        assert_eq!(err.to_string(), "Cannot move a model from Inactive to Error");
+    }
+
+    // Resolves after being polled a given number of times
+    struct Countdown(usize, Option<Map>);
+
+    impl Future for Countdown {
+        type Output = Result<Map, String>;
+
+        fn poll(mut self: Pin<&mut Self>, _context: &mut Context) -> Poll<Self::Output> {
+            if self.0 == 0 {
+                Poll::Ready(self.1.take().ok_or_else(|| "No map".to_string()))
+            } else {
+                self.0 -= 1;
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn background_load_activates() {
+        let mut elem = with_inner();
+        let (release, gate) = channel::<()>();
+
+        elem.load_in_background(move || {
+            gate.recv().unwrap();
+            MapBuilder::from_named(None)
+        }).unwrap();
+
+        assert_eq!(elem.state(), Loading);
+        assert_eq!(elem.poll_load(), Ok(LoadStatus::Pending));
+
+        release.send(()).unwrap();
+
+        assert_eq!(elem.wait_for_load(), Ok(LoadStatus::Loaded));
+        assert_eq!(elem.state(), Active);
+        assert_eq!(inner_state(&elem), Active);
+        assert_eq!(elem.subject().unwrap().name(), "Tutorial");
+        assert_eq!(elem.poll_load(), Ok(LoadStatus::Idle));
+    }
+
+    #[test]
+    fn failed_load_moves_to_error() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.load_in_background(|| Err("Bad map".to_string())).unwrap();
+
+        assert_eq!(elem.wait_for_load(), Ok(LoadStatus::Failed("Bad map".to_string())));
+        assert_eq!(elem.state(), Error);
+        assert!(elem.subject().is_none());
+    }
+
+    #[test]
+    fn future_load() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.load_future(Countdown(2, Some(tutorial()))).unwrap();
+
+        assert_eq!(elem.poll_load(), Ok(LoadStatus::Pending));
+        assert_eq!(elem.poll_load(), Ok(LoadStatus::Pending));
+        assert_eq!(elem.poll_load(), Ok(LoadStatus::Loaded));
+        assert_eq!(elem.state(), Active);
+    }
+
+    #[test]
+    fn changing_subject_cancels_load() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.load_future(Countdown(5, Some(tutorial()))).unwrap();
+        elem.change_subject(Some(MapBuilder::from_named(Some(MapName::Danger)).unwrap())).unwrap();
+
+        assert!(!elem.is_loading_subject());
+        assert_eq!(elem.state(), Active);
+        assert_eq!(elem.subject().unwrap().name(), "Danger");
+        assert_eq!(elem.wait_for_load(), Ok(LoadStatus::Idle));
+    }
+
+    #[test]
+    fn cancel_background_load() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.load_in_background(|| MapBuilder::from_named(None)).unwrap();
+
+        assert_eq!(elem.cancel_load(), Ok(true));
+        assert_eq!(elem.poll_load(), Ok(LoadStatus::Idle));
+        assert_eq!(elem.state(), Inactive);
+        assert_eq!(elem.cancel_load(), Ok(false));
+    }
+
+    #[test]
+    fn cancel_returns_to_previous_state() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.activate().unwrap();
+        elem.load_future(future::pending()).unwrap();
+
+        assert_eq!(elem.state(), Loading);
+        assert_eq!(elem.cancel_load(), Ok(true));
+        assert_eq!(elem.state(), Active);
+        assert_eq!(elem.subject().unwrap().name(), "Tutorial");
+    }
+
+    #[test]
+    fn cancel_after_reloading_returns_to_first_state() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.activate().unwrap();
+        elem.load_future(future::pending()).unwrap();
+        elem.load_future(future::pending()).unwrap();
+
+        assert_eq!(elem.cancel_load(), Ok(true));
+        assert_eq!(elem.state(), Active);
+    }
+
+    #[test]
+    fn clones_made_while_loading_fall_back() {
+        let mut elem: ViewModel<Map> = ViewModel::new(Some(InnerModel::new()));
+
+        elem.activate().unwrap();
+        elem.load_future(future::pending()).unwrap();
+
+        let mut copy = elem.clone();
+
+        assert_eq!(copy.state(), Active);
+        assert_eq!(inner_state(&copy), Active);
+        assert!(!copy.is_loading_subject());
+        assert_eq!(copy.poll_load(), Ok(LoadStatus::Idle));
+        assert_eq!(elem.state(), Loading);
+        assert!(elem.is_loading_subject());
+    }
+
+    // Resolves once a helper thread has flipped the flag and woken the waiter
+    struct WokenLater {
+        _done: Arc<AtomicBool>,
+        _started: bool,
+    }
+
+    impl Future for WokenLater {
+        type Output = LoadResult<Map>;
+
+        fn poll(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+            if self._done.load(Ordering::SeqCst) {
+                return Poll::Ready(Ok(tutorial()));
+            }
+
+            if !self._started {
+                self._started = true;
+
+                let done = self._done.clone();
+                let waker = context.waker().clone();
+
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(20));
+                    done.store(true, Ordering::SeqCst);
+                    waker.wake();
+                });
+            }
+
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn wait_sleeps_until_woken() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.load_future(WokenLater { _done: Arc::new(AtomicBool::new(false)), _started: false }).unwrap();
+
+        assert_eq!(elem.wait_for_load(), Ok(LoadStatus::Loaded));
+        assert_eq!(elem.state(), Active);
+    }
+
+    fn named(name: MapName) -> Map {
+        MapBuilder::from_named(Some(name)).unwrap()
+    }
+
+    #[test]
+    fn history_on_by_default() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        assert_eq!(elem.history().limit(), DEFAULT_HISTORY_LIMIT);
+
+        elem.change_subject(Some(tutorial())).unwrap();
+
+        assert!(elem.can_undo());
+        assert_eq!(elem.undo(), Ok(true));
+        assert!(elem.subject().is_none());
+    }
+
+    #[test]
+    fn history_can_be_turned_off() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.set_history_limit(0);
+        elem.change_subject(Some(named(MapName::Danger))).unwrap();
+
+        assert!(!elem.can_undo());
+        assert_eq!(elem.undo(), Ok(false));
+    }
+
+    #[test]
+    fn loaded_subjects_can_be_undone() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.change_subject(Some(named(MapName::Danger))).unwrap();
+        elem.load_future(future::ready(Ok(tutorial()))).unwrap();
+        elem.wait_for_load().unwrap();
+
+        assert_eq!(elem.history().undo_entries().len(), 3);
+        assert_eq!(elem.undo(), Ok(true));
+        assert_eq!(elem.subject().unwrap().name(), "Danger");
+        assert_eq!(elem.undo(), Ok(true));
+        assert_eq!(elem.subject().unwrap().name(), "Tutorial");
+        assert_eq!(elem.undo(), Ok(true));
+        assert!(elem.subject().is_none());
+        assert_eq!(elem.undo(), Ok(false));
+    }
+
+    #[test]
+    fn entries_that_change_nothing_are_skipped() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.change_subject(Some(named(MapName::Danger))).unwrap();
+        elem.History._undo.push_back(Change::Subject(Some(named(MapName::Danger))));
+
+        assert!(elem.can_undo());
+        assert_eq!(elem.undo(), Ok(true));
+        assert_eq!(elem.subject().unwrap().name(), "Tutorial");
+
+        elem.History._undo.push_back(Change::Subject(Some(tutorial())));
+
+        assert!(elem.can_undo());
+        assert_eq!(elem.undo(), Ok(true));
+        assert!(elem.subject().is_none());
+
+        elem.History._undo.push_back(Change::Subject(None));
+
+        assert!(!elem.can_undo());
+        assert_eq!(elem.undo(), Ok(false));
+        assert!(elem.history().undo_entries().is_empty());
+    }
+
+    #[test]
+    fn undo_and_redo_subjects() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.set_history_limit(10);
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.change_subject(Some(named(MapName::Danger))).unwrap();
+
+        assert_eq!(elem.history().undo_entries(), vec![HistoryKind::Subject, HistoryKind::Subject]);
+        assert_eq!(elem.undo(), Ok(true));
+        assert_eq!(elem.subject().unwrap().name(), "Tutorial");
+        assert_eq!(elem.undo(), Ok(true));
+        assert!(elem.subject().is_none());
+        assert_eq!(elem.undo(), Ok(false));
+        assert_eq!(elem.history().redo_entries().len(), 2);
+        assert_eq!(elem.redo(), Ok(true));
+        assert_eq!(elem.subject().unwrap().name(), "Tutorial");
+
+        // A fresh change forgets what could be redone
+        elem.change_subject(Some(named(MapName::Spiral))).unwrap();
+
+        assert!(!elem.can_redo());
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.set_history_limit(2);
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.change_subject(Some(named(MapName::Danger))).unwrap();
+        elem.change_subject(Some(named(MapName::Spiral))).unwrap();
+
+        assert_eq!(elem.history().undo_entries().len(), 2);
+
+        elem.undo().unwrap();
+        elem.undo().unwrap();
+
+        assert_eq!(elem.subject().unwrap().name(), "Tutorial");
+        assert!(!elem.can_undo());
+    }
+
+    #[test]
+    fn undo_model_respects_lifecycle() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+        let events = elem.subscribe();
+
+        elem.set_history_limit(5);
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.activate().unwrap();
+        elem.change_model(Some(InnerModel::new())).unwrap();
+
+        assert_eq!(elem.history().undo_entries()[0], HistoryKind::Model);
+
+        let _ = events.try_iter().count();
+
+        elem.undo().unwrap();
+
+        assert!(elem.inner().is_none());
+        assert_eq!(elem.state(), Active);
+        assert_eq!(
+            events.try_iter().collect::<Vec<ModelEvent>>(),
+            vec![
+                ModelEvent::StateChanged(Active, Loading),
+                ModelEvent::ModelChanged,
+                ModelEvent::StateChanged(Loading, Active),
+            ]
+        );
+
+        elem.redo().unwrap();
+
+        assert_eq!(inner_state(&elem), Active);
+    }
+
+    #[test]
+    fn no_undo_once_disposed() {
+        let mut elem: ViewModel<Map> = ViewModel::new(None);
+
+        elem.set_history_limit(5);
+        elem.change_subject(Some(tutorial())).unwrap();
+        elem.dispose().unwrap();
+
+        assert!(elem.undo().is_err());
+        assert!(elem.can_undo());
+    }
+
+    // course -> hole -> ball, with every transition logged as "name:state"
+    fn course_tree(log: &Rc<RefCell<Vec<String>>>) -> ViewModel<String> {
+        fn logged<T>(model: &mut ViewModel<T>, name: &'static str, log: &Rc<RefCell<Vec<String>>>) {
+            for state in &[Inactive, Loading, Active, Error, Disposed] {
+                let log = log.clone();
+                model.on_transition(*state, Rc::new(move |_, to| log.borrow_mut().push(format!("{}:{:?}", name, to))));
+            }
+        }
+
+        let mut course: ViewModel<String> = ViewModel::new(None);
+        let mut hole: ViewModel<Map> = ViewModel::new(None);
+        let mut ball: ViewModel<(usize, usize)> = ViewModel::new(None);
+        let mut flag: ViewModel<(usize, usize)> = ViewModel::new(None);
+
+        logged(&mut course, "course", log);
+        logged(&mut hole, "hole", log);
+        logged(&mut ball, "ball", log);
+        logged(&mut flag, "flag", log);
+
+        hole.add_child("ball", ball).unwrap();
+        hole.add_child("flag", flag).unwrap();
+        course.add_child("hole", hole).unwrap();
+
+        course
+    }
+
+    #[test]
+    fn activation_cascades_in_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut course = course_tree(&log);
+
+        course.activate().unwrap();
+
+        assert_eq!(*log.borrow(), vec!["course:Active", "hole:Active", "ball:Active", "flag:Active"]);
+
+        log.borrow_mut().clear();
+        course.deactivate().unwrap();
+
+        assert_eq!(*log.borrow(), vec!["flag:Inactive", "ball:Inactive", "hole:Inactive", "course:Inactive"]);
+    }
+
+    #[test]
+    fn blocked_child_stops_the_cascade() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut course = course_tree(&log);
+
+        course.child_as_mut::<ViewModel<Map>>("hole").unwrap()
+            .child_mut("ball").unwrap()
+            .enter(Disposed).unwrap();
+        log.borrow_mut().clear();
+
+        assert!(course.activate().is_err());
+        assert!(log.borrow().is_empty());
+        assert_eq!(course.state(), Inactive);
+    }
+
+    #[test]
+    fn children_bubble_events() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut course = course_tree(&log);
+        let events = course.subscribe();
+
+        course.child_as_mut::<ViewModel<Map>>("hole").unwrap()
+            .child_as_mut::<ViewModel<(usize, usize)>>("ball").unwrap()
+            .change_subject(Some((3, 4))).unwrap();
+
+        assert_eq!(
+            events.try_iter().collect::<Vec<ModelEvent>>(),
+            vec![
+                ModelEvent::Child(
+                    "hole".to_string(),
+                    Box::new(ModelEvent::Child("ball".to_string(), Box::new(ModelEvent::SubjectChanged)))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn added_children_join_parent_state() {
+        let mut course: ViewModel<String> = ViewModel::new(None);
+
+        course.activate().unwrap();
+        course.add_child("hole", ViewModel::<Map>::new(None)).unwrap();
+
+        assert_eq!(course.child("hole").unwrap().state(), Active);
+        assert_eq!(course.children().names(), vec!["hole"]);
+    }
+
+    #[test]
+    fn removed_children_stop_bubbling() {
+        let mut course: ViewModel<String> = ViewModel::new(None);
+        let events = course.subscribe();
+
+        course.add_child("hole", ViewModel::<Map>::new(None)).unwrap();
+
+        let mut hole = course.remove_child("hole").unwrap();
+
+        hole.enter(Active).unwrap();
+
+        assert!(course.children().is_empty());
+        assert_eq!(events.try_iter().count(), 0);
+        assert_eq!(course.state(), Inactive);
+    }
+}
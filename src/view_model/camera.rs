@@ -0,0 +1,184 @@
+use model::map::Map;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CameraMode {
+    // Stays where it was last panned to
+    Manual,
+    // Keeps the followed position centred as far as the map edges allow
+    Follow,
+}
+
+// The window of the board a View shows, in map cells
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Viewport {
+    _x: usize,
+    _y: usize,
+    _width: usize,
+    _height: usize,
+}
+
+impl Viewport {
+    pub fn x(&self) -> usize {
+        self._x
+    }
+
+    pub fn y(&self) -> usize {
+        self._y
+    }
+
+    pub fn width(&self) -> usize {
+        self._width
+    }
+
+    pub fn height(&self) -> usize {
+        self._height
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self._x && x < self._x + self._width && y >= self._y && y < self._y + self._height
+    }
+
+    // Map coordinates to coordinates within the view, None when clipped
+    pub fn to_screen(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if self.contains(x, y) {
+            Some((x - self._x, y - self._y))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Camera {
+    _x: usize,
+    _y: usize,
+    _mode: CameraMode,
+}
+
+impl Default for Camera {
+    fn default() -> Camera {
+        Camera::new()
+    }
+}
+
+impl Camera {
+    pub fn new() -> Camera {
+        Camera {
+            _x: 0,
+            _y: 0,
+            _mode: CameraMode::Manual,
+        }
+    }
+
+    // Top left cell of the map shown
+    pub fn position(&self) -> (usize, usize) {
+        (self._x, self._y)
+    }
+
+    pub fn mode(&self) -> CameraMode {
+        self._mode
+    }
+
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        self._mode = mode;
+    }
+
+    // Moves the camera by whole cells, stopping at the map edges. Panning by
+    // hand stops following the ball.
+    pub fn pan(&mut self, dx: isize, dy: isize, size: (usize, usize), map: &Map) {
+        self._mode = CameraMode::Manual;
+        self._x = (self._x as isize).saturating_add(dx).max(0) as usize;
+        self._y = (self._y as isize).saturating_add(dy).max(0) as usize;
+        self.clamp(size, map);
+    }
+
+    // Centres on the target if the camera is following, e.g. the ball after each shot
+    pub fn follow(&mut self, target: (usize, usize), size: (usize, usize), map: &Map) {
+        if self._mode != CameraMode::Follow {
+            return;
+        }
+
+        self._x = target.0.saturating_sub(size.0 / 2);
+        self._y = target.1.saturating_sub(size.1 / 2);
+        self.clamp(size, map);
+    }
+
+    // The visible window for a view of the given size, in cells
+    pub fn viewport(&self, size: (usize, usize), map: &Map) -> Viewport {
+        let width = size.0.min(map.width());
+        let height = size.1.min(map.length());
+
+        Viewport {
+            _x: self._x.min(map.width() - width),
+            _y: self._y.min(map.length() - height),
+            _width: width,
+            _height: height,
+        }
+    }
+
+    fn clamp(&mut self, size: (usize, usize), map: &Map) {
+        self._x = self._x.min(map.width().saturating_sub(size.0));
+        self._y = self._y.min(map.length().saturating_sub(size.1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Camera, CameraMode};
+    use model::map::{MapBuilder, MapName};
+
+    #[test]
+    fn viewport_clipped_to_map() {
+        let map = MapBuilder::from_named(Some(MapName::Reverse)).unwrap();
+        let test = Camera::new().viewport((80, 10), &map);
+
+        assert_eq!(test.width(), map.width());
+        assert_eq!(test.height(), 10);
+        assert_eq!(test.to_screen(3, 12), None);
+        assert_eq!(test.to_screen(3, 9), Some((3, 9)));
+    }
+
+    #[test]
+    fn panning_stops_at_edges() {
+        let map = MapBuilder::from_named(None).unwrap();
+        let mut test = Camera::new();
+
+        test.set_mode(CameraMode::Follow);
+        test.pan(-5, 3, (10, 5), &map);
+
+        assert_eq!(test.position(), (0, 3));
+        assert_eq!(test.mode(), CameraMode::Manual);
+
+        test.pan(100, 100, (10, 5), &map);
+
+        assert_eq!(test.position(), (24, 7));
+
+        test.pan(isize::MAX, isize::MAX, (10, 5), &map);
+
+        assert_eq!(test.position(), (24, 7));
+
+        test.pan(isize::MIN, isize::MIN, (10, 5), &map);
+
+        assert_eq!(test.position(), (0, 0));
+    }
+
+    #[test]
+    fn follows_the_ball() {
+        let map = MapBuilder::from_named(None).unwrap();
+        let mut test = Camera::new();
+
+        test.follow((20, 6), (10, 4), &map);
+
+        assert_eq!(test.position(), (0, 0));
+
+        test.set_mode(CameraMode::Follow);
+        test.follow((20, 6), (10, 4), &map);
+
+        assert_eq!(test.position(), (15, 4));
+        assert!(test.viewport((10, 4), &map).contains(20, 6));
+
+        test.follow((33, 11), (10, 4), &map);
+
+        assert_eq!(test.position(), (24, 8));
+    }
+}
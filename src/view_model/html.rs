@@ -0,0 +1,146 @@
+use std::fmt::Write;
+
+use model::course::Course;
+use model::map::Map;
+use model::score_card::ScoreCard;
+use view_model::svg::SvgRenderer;
+use view_model::view_base::escape;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em}table{border-collapse:collapse}th,td{border:1px solid #999;padding:4px 8px;text-align:center}.eagle{background:#ffd54f}.birdie{background:#e57373}.bogey{background:#90caf9}.double-bogey{background:#1e88e5;color:#fff}.holes{display:flex;flex-wrap:wrap;gap:1em}figure{margin:0}";
+const HOLE_CELL_SIZE: usize = 8;
+
+fn score_class(strokes: i32, par: i32) -> &'static str {
+    match strokes - par {
+        diff if diff <= -2 => "eagle",
+        -1 => "birdie",
+        0 => "par",
+        1 => "bogey",
+        _ => "double-bogey",
+    }
+}
+
+fn to_par(diff: i32) -> String {
+    match diff {
+        0 => "E".to_string(),
+        diff if diff > 0 => format!("+{}", diff),
+        diff => diff.to_string(),
+    }
+}
+
+// A self-contained HTML summary of a finished round: the scorecard for every
+// player, and a picture of each hole whose map is given (matched by name)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HtmlReport;
+
+impl HtmlReport {
+    pub fn render(&self, course: &Course, maps: &[Map], cards: &[&ScoreCard]) -> String {
+        let mut html = String::new();
+        let title = escape(course.name());
+
+        // Writing to a String can't fail
+        let _ = self.write_report(&mut html, &title, course, maps, cards);
+
+        html
+    }
+
+    fn write_report(&self, html: &mut String, title: &str, course: &Course, maps: &[Map], cards: &[&ScoreCard]) -> ::std::fmt::Result {
+        writeln!(html, "<!DOCTYPE html>")?;
+        writeln!(html, "<html><head><meta charset=\"utf-8\"><title>{}</title><style>{}</style></head><body>", title, STYLE)?;
+        writeln!(html, "<h1>{}</h1>", title)?;
+
+        writeln!(html, "<table class=\"scorecard\">")?;
+        write!(html, "<tr><th>Hole</th>")?;
+        for idx in 1..=course.holes().len() {
+            write!(html, "<th>{}</th>", idx)?;
+        }
+        writeln!(html, "<th>Total</th><th>To par</th></tr>")?;
+
+        write!(html, "<tr><th>Par</th>")?;
+        for hole in course.holes() {
+            write!(html, "<td>{}</td>", hole.par())?;
+        }
+        writeln!(html, "<td>{}</td><td></td></tr>", course.par())?;
+
+        for card in cards {
+            write!(html, "<tr><th>{}</th>", escape(card.name()))?;
+
+            for (idx, hole) in course.holes().iter().enumerate() {
+                match card.strokes().get(idx) {
+                    Some(&strokes) => write!(html, "<td class=\"{}\">{}</td>", score_class(strokes, hole.par()), strokes)?,
+                    None => write!(html, "<td></td>")?,
+                }
+            }
+
+            writeln!(html, "<td>{}</td><td>{}</td></tr>", card.gross(), to_par(card.differential()))?;
+        }
+
+        writeln!(html, "</table>")?;
+
+        writeln!(html, "<div class=\"holes\">")?;
+        for (idx, hole) in course.holes().iter().enumerate() {
+            if let Some(map) = maps.iter().find(|map| map.name() == hole.name()) {
+                writeln!(html, "<figure>")?;
+                write!(html, "{}", SvgRenderer::new(HOLE_CELL_SIZE).export(map))?;
+                writeln!(html, "<figcaption>{}. {} (par {})</figcaption>", idx + 1, escape(hole.name()), hole.par())?;
+                writeln!(html, "</figure>")?;
+            }
+        }
+        writeln!(html, "</div>")?;
+
+        writeln!(html, "</body></html>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, HtmlReport};
+    use model::course::{Course, Hole};
+    use model::map::{MapBuilder, MapName};
+    use model::score_card::ScoreCard;
+
+    #[test]
+    fn escapes_names() {
+        assert_eq!(escape("<Bob & \"Al\">"), "&lt;Bob &amp; &quot;Al&quot;&gt;");
+    }
+
+    #[test]
+    fn round_report() {
+        let course = Course::new(
+            "Office <Open>".to_string(),
+            vec![
+                Hole::new("Tutorial".to_string(), 3, 2),
+                Hole::new("Danger".to_string(), 4, 1),
+                Hole::new("Missing".to_string(), 3, 3),
+            ]
+        ).unwrap();
+        let maps = vec![
+            MapBuilder::from_named(None).unwrap(),
+            MapBuilder::from_named(Some(MapName::Danger)).unwrap(),
+        ];
+        let first = ScoreCard::from_strokes("First".to_string(), &course, vec![2, 5, 3]);
+        let second = ScoreCard::from_strokes("Second".to_string(), &course, vec![1, 7, 3]);
+
+        let test = HtmlReport.render(&course, &maps, &[&first, &second]);
+
+        assert!(test.starts_with("<!DOCTYPE html>"));
+        assert!(test.contains("<h1>Office &lt;Open&gt;</h1>"));
+        assert!(test.contains("<tr><th>First</th><td class=\"birdie\">2</td><td class=\"bogey\">5</td><td class=\"par\">3</td><td>10</td><td>E</td></tr>"));
+        assert!(test.contains("<td class=\"eagle\">1</td><td class=\"double-bogey\">7</td>"));
+        assert_eq!(test.matches("<svg ").count(), 2);
+        assert!(test.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn hostile_map_names_are_escaped() {
+        let hostile = "<script>alert(1)</script>";
+        let course = Course::new("Open".to_string(), vec![Hole::new(hostile.to_string(), 3, 1)]).unwrap();
+        let map = MapBuilder::from_text("", &format!("GolfMania map v2\nname: {}\n---\nHHHH\nH*#H\nHHHH", hostile)).unwrap();
+        let card = ScoreCard::from_strokes("First".to_string(), &course, vec![2]);
+
+        let test = HtmlReport.render(&course, &[map], &[&card]);
+
+        assert_eq!(test.matches("<svg ").count(), 1);
+        assert!(test.contains("<title>&lt;script&gt;alert(1)&lt;/script&gt;</title>"));
+        assert!(!test.contains("<script"));
+    }
+}
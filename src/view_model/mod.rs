@@ -1,2 +1,8 @@
 pub mod view_base;
-pub mod view_container_factory;
\ No newline at end of file
+pub mod camera;
+pub mod view_container_factory;
+pub mod terminal;
+pub mod svg;
+pub mod html;
+#[cfg(feature = "raster")]
+pub mod raster;
\ No newline at end of file
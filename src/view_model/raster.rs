@@ -0,0 +1,450 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use model::map::{Map, Terrain};
+use view_model::view_base::{overlay_at, Overlay, OverlayKind, View, ViewRenderer};
+
+pub type Rgb = [u8; 3];
+
+// Largest stored (uncompressed) deflate block
+const STORED_BLOCK: usize = 65535;
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    _terrain: BTreeMap<Terrain, Rgb>,
+    _ball: Rgb,
+    _player: Rgb,
+}
+
+impl Default for Palette {
+    fn default() -> Palette {
+        let mut terrain = BTreeMap::new();
+
+        terrain.insert(Terrain::Rough, [46, 125, 50]);
+        terrain.insert(Terrain::Fairway, [156, 204, 101]);
+        terrain.insert(Terrain::Tee, [224, 224, 224]);
+        terrain.insert(Terrain::Hole, [0, 0, 0]);
+
+        for wall in &[Terrain::WallVertical, Terrain::WallHorizontal, Terrain::WallBackslash, Terrain::WallSlash] {
+            terrain.insert(*wall, [255, 255, 255]);
+        }
+
+        Palette {
+            _terrain: terrain,
+            _ball: [255, 255, 255],
+            _player: [255, 235, 59],
+        }
+    }
+}
+
+impl Palette {
+    pub fn colour(&self, terrain: Terrain) -> Rgb {
+        self._terrain[&terrain]
+    }
+
+    pub fn set_colour(&mut self, terrain: Terrain, colour: Rgb) {
+        self._terrain.insert(terrain, colour);
+    }
+
+    pub fn ball(&self) -> Rgb {
+        self._ball
+    }
+
+    pub fn set_ball(&mut self, colour: Rgb) {
+        self._ball = colour;
+    }
+
+    pub fn player(&self) -> Rgb {
+        self._player
+    }
+
+    pub fn set_player(&mut self, colour: Rgb) {
+        self._player = colour;
+    }
+}
+
+// A pixel grid, row by row
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    _width: usize,
+    _height: usize,
+    _pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn width(&self) -> usize {
+        self._width
+    }
+
+    pub fn height(&self) -> usize {
+        self._height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        self._pixels[y * self._width + x]
+    }
+}
+
+// Software rendering of maps to PNG, and of shots to animated GIF, at a
+// whole number of pixels per cell
+#[derive(Clone, Debug, PartialEq)]
+pub struct RasterRenderer {
+    _scale: usize,
+    _palette: Palette,
+}
+
+impl RasterRenderer {
+    pub fn new(scale: usize) -> RasterRenderer {
+        RasterRenderer {
+            _scale: scale.max(1),
+            _palette: Palette::default(),
+        }
+    }
+
+    pub fn scale(&self) -> usize {
+        self._scale
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self._palette
+    }
+
+    pub fn palette_mut(&mut self) -> &mut Palette {
+        &mut self._palette
+    }
+
+    pub fn rasterize(&self, view: &View, map: &Map, overlays: &[Overlay]) -> Image {
+        let viewport = view.viewport(map);
+        let scale = self._scale;
+        let width = viewport.width() * scale;
+        let height = viewport.height() * scale;
+        let board = map.board();
+        let mut pixels = vec![[0u8; 3]; width * height];
+
+        for (y, row) in board.iter().enumerate().skip(viewport.y()).take(viewport.height()) {
+            for (x, cell) in row.iter().enumerate().skip(viewport.x()).take(viewport.width()) {
+                let terrain = cell.terrain();
+                let overlay = overlay_at(overlays, x, y);
+
+                for py in 0..scale {
+                    for px in 0..scale {
+                        let colour = match overlay {
+                            Some(overlay) if RasterRenderer::in_disc(px, py, scale) => match overlay.kind() {
+                                OverlayKind::Ball => self._palette.ball(),
+                                OverlayKind::Player(_) => self._palette.player(),
+                            },
+                            _ => self.cell_pixel(terrain, px, py),
+                        };
+
+                        pixels[((y - viewport.y()) * scale + py) * width + (x - viewport.x()) * scale + px] = colour;
+                    }
+                }
+            }
+        }
+
+        Image {
+            _width: width,
+            _height: height,
+            _pixels: pixels,
+        }
+    }
+
+    // The whole map as a PNG file
+    pub fn render_png(&self, map: &Map, overlays: &[Overlay]) -> Vec<u8> {
+        let view = View::new(map.width() as isize, map.length() as isize);
+        encode_png(&self.rasterize(&view, map, overlays))
+    }
+
+    // An animated GIF with one frame per ball position along the shot,
+    // each shown for delay hundredths of a second
+    pub fn render_shot_gif(&self, map: &Map, path: &[(usize, usize)], delay: u16) -> Vec<u8> {
+        let view = View::new(map.width() as isize, map.length() as isize);
+        let frames: Vec<Image> = path
+            .iter()
+            .map(|&(x, y)| self.rasterize(&view, map, &[Overlay::ball(x, y)]))
+            .collect();
+
+        encode_gif(&frames, delay)
+    }
+
+    // Walls are drawn as a line over the fairway, everything else is a flat fill
+    fn cell_pixel(&self, terrain: Terrain, px: usize, py: usize) -> Rgb {
+        let last = self._scale - 1;
+        let on_wall = match terrain {
+            Terrain::WallVertical => px == self._scale / 2,
+            Terrain::WallHorizontal => py == last,
+            Terrain::WallBackslash => px == py,
+            Terrain::WallSlash => px == last - py,
+            _ => return self._palette.colour(terrain),
+        };
+
+        if on_wall {
+            self._palette.colour(terrain)
+        } else {
+            self._palette.colour(Terrain::Fairway)
+        }
+    }
+
+    fn in_disc(px: usize, py: usize, scale: usize) -> bool {
+        let centre = scale as f32 / 2.0;
+        let dx = px as f32 + 0.5 - centre;
+        let dy = py as f32 + 0.5 - centre;
+
+        dx * dx + dy * dy <= centre * centre * 0.5
+    }
+}
+
+impl ViewRenderer for RasterRenderer {
+    // Writes the visible window as PNG
+    fn display<W: Write>(&self, view: &View, map: &Map, overlays: &[Overlay], target: &mut W) -> io::Result<()> {
+        target.write_all(&encode_png(&self.rasterize(view, map, overlays)))
+    }
+}
+
+fn crc32(chunks: &[&[u8]]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+
+    for byte in chunks.iter().flat_map(|chunk| chunk.iter()) {
+        crc ^= u32::from(*byte);
+
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    for byte in data {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
+
+fn png_chunk(output: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    output.extend_from_slice(kind);
+    output.extend_from_slice(data);
+    output.extend_from_slice(&crc32(&[kind, data]).to_be_bytes());
+}
+
+// Truecolour PNG, the image data stored in uncompressed deflate blocks
+pub fn encode_png(image: &Image) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((image.width() * 3 + 1) * image.height());
+
+    for row in image._pixels.chunks(image.width().max(1)).take(image.height()) {
+        // Filter type 0, no filtering
+        raw.push(0);
+
+        for pixel in row {
+            raw.extend_from_slice(pixel);
+        }
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if raw.is_empty() { vec![&[]] } else { raw.chunks(STORED_BLOCK).collect() };
+
+    for (idx, block) in blocks.iter().enumerate() {
+        let length = block.len() as u16;
+
+        zlib.push((idx + 1 == blocks.len()) as u8);
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(image.width() as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height() as u32).to_be_bytes());
+    // 8 bits per channel, RGB, deflate, adaptive filtering, no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut output = PNG_SIGNATURE.to_vec();
+    png_chunk(&mut output, b"IHDR", &header);
+    png_chunk(&mut output, b"IDAT", &zlib);
+    png_chunk(&mut output, b"IEND", &[]);
+
+    output
+}
+
+// Packs variable width codes least significant bit first, as GIF expects
+struct CodeWriter {
+    _bytes: Vec<u8>,
+    _buffer: u32,
+    _bits: u32,
+}
+
+impl CodeWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self._buffer |= u32::from(code) << self._bits;
+        self._bits += width;
+
+        while self._bits >= 8 {
+            self._bytes.push(self._buffer as u8);
+            self._buffer >>= 8;
+            self._bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self._bits > 0 {
+            self._bytes.push(self._buffer as u8);
+        }
+
+        self._bytes
+    }
+}
+
+// LZW without the compression: a clear code is sent often enough that the
+// dictionary never grows past the starting code width
+fn gif_image_data(indices: &[u8], min_code_size: u32) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let width = min_code_size + 1;
+    let run = (clear - 2) as usize;
+    let mut writer = CodeWriter { _bytes: Vec::new(), _buffer: 0, _bits: 0 };
+
+    for chunk in indices.chunks(run) {
+        writer.write(clear, width);
+
+        for &index in chunk {
+            writer.write(u16::from(index), width);
+        }
+    }
+
+    writer.write(clear + 1, width);
+
+    let data = writer.finish();
+    let mut output = vec![min_code_size as u8];
+
+    for block in data.chunks(255) {
+        output.push(block.len() as u8);
+        output.extend_from_slice(block);
+    }
+
+    output.push(0);
+    output
+}
+
+// Looping animated GIF; all frames share one global colour table
+pub fn encode_gif(frames: &[Image], delay: u16) -> Vec<u8> {
+    let mut colours: Vec<Rgb> = Vec::new();
+
+    for pixel in frames.iter().flat_map(|frame| frame._pixels.iter()) {
+        if !colours.contains(pixel) && colours.len() < 256 {
+            colours.push(*pixel);
+        }
+    }
+
+    let mut table_bits = 1;
+    while (1 << table_bits) < colours.len() {
+        table_bits += 1;
+    }
+
+    let (width, height) = match frames.first() {
+        Some(frame) => (frame.width() as u16, frame.height() as u16),
+        None => (0, 0),
+    };
+
+    let mut output = b"GIF89a".to_vec();
+    output.extend_from_slice(&width.to_le_bytes());
+    output.extend_from_slice(&height.to_le_bytes());
+    output.push(0x80 | ((table_bits - 1) << 4) as u8 | (table_bits - 1) as u8);
+    output.extend_from_slice(&[0, 0]);
+
+    for idx in 0..(1 << table_bits) {
+        output.extend_from_slice(colours.get(idx).unwrap_or(&[0, 0, 0]));
+    }
+
+    // Loop forever
+    output.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+    output.extend_from_slice(b"NETSCAPE2.0");
+    output.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    for frame in frames {
+        output.extend_from_slice(&[0x21, 0xF9, 0x04, 0x00]);
+        output.extend_from_slice(&delay.to_le_bytes());
+        output.extend_from_slice(&[0x00, 0x00]);
+
+        output.push(0x2C);
+        output.extend_from_slice(&[0, 0, 0, 0]);
+        output.extend_from_slice(&(frame.width() as u16).to_le_bytes());
+        output.extend_from_slice(&(frame.height() as u16).to_le_bytes());
+        output.push(0);
+
+        let indices: Vec<u8> = frame._pixels
+            .iter()
+            .map(|pixel| colours.iter().position(|colour| colour == pixel).unwrap_or(0) as u8)
+            .collect();
+
+        output.extend_from_slice(&gif_image_data(&indices, table_bits.max(2)));
+    }
+
+    output.push(0x3B);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32, Palette, RasterRenderer};
+    use model::map::{MapBuilder, Terrain};
+    use view_model::view_base::{Overlay, View};
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(&[b"IEND"]), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn rasterizes_at_scale() {
+        let map = MapBuilder::from_named(None).unwrap();
+        let test = RasterRenderer::new(4).rasterize(&View::new(34, 12), &map, &[Overlay::ball(8, 4)]);
+        let palette = Palette::default();
+
+        assert_eq!((test.width(), test.height()), (136, 48));
+        assert_eq!(test.pixel(0, 0), palette.colour(Terrain::Rough));
+        assert_eq!(test.pixel(34, 18), palette.ball());
+        // '|' at (6, 3) has its wall line down the middle of the cell
+        assert_eq!(test.pixel(26, 12), palette.colour(Terrain::WallVertical));
+        assert_eq!(test.pixel(24, 12), palette.colour(Terrain::Fairway));
+    }
+
+    #[test]
+    fn custom_palette() {
+        let map = MapBuilder::from_named(None).unwrap();
+        let mut test = RasterRenderer::new(1);
+
+        test.palette_mut().set_colour(Terrain::Rough, [1, 2, 3]);
+
+        assert_eq!(test.rasterize(&View::new(2, 2), &map, &[]).pixel(1, 1), [1, 2, 3]);
+    }
+
+    #[test]
+    fn png_layout() {
+        let map = MapBuilder::from_named(None).unwrap();
+        let test = RasterRenderer::new(2).render_png(&map, &[]);
+
+        assert_eq!(&test[..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
+        assert_eq!(&test[12..16], b"IHDR");
+        assert_eq!(&test[16..24], &[0, 0, 0, 68, 0, 0, 0, 24]);
+        assert_eq!(&test[test.len() - 8..test.len() - 4], b"IEND");
+    }
+
+    #[test]
+    fn gif_layout() {
+        let map = MapBuilder::from_named(None).unwrap();
+        let test = RasterRenderer::new(2).render_shot_gif(&map, &[(9, 3), (12, 4), (20, 4)], 10);
+
+        assert_eq!(&test[..6], b"GIF89a");
+        assert_eq!(&test[6..10], &[68, 0, 24, 0]);
+        assert_eq!(test.last(), Some(&0x3B));
+    }
+}
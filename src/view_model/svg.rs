@@ -0,0 +1,214 @@
+use std::io::{self, Write};
+
+use model::map::{Map, Terrain};
+use view_model::view_base::{escape, Overlay, OverlayKind, View, ViewRenderer};
+
+const STYLE: &str = ".rough{fill:#2e7d32}.fairway{fill:#9ccc65}.tee-ground{fill:#aed581}.hole-ground{fill:#9ccc65}.wall-vertical,.wall-horizontal,.wall-backslash,.wall-slash{fill:#9ccc65}.wall{stroke:#ffffff;stroke-width:2;stroke-linecap:square}.hole{fill:#000000}.tee{fill:#ffffff;stroke:#000000}.ball{fill:#ffffff;stroke:#000000}.player{fill:#ffeb3b;stroke:#000000}.shot{fill:none;stroke:#ffffff;stroke-dasharray:4 2}";
+
+// Writes the visible part of a map as an SVG document, walls as line
+// segments and optionally the paths of recorded shots over the top
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgRenderer {
+    _cell_size: usize,
+    _shots: Vec<Vec<(f32, f32)>>,
+}
+
+impl SvgRenderer {
+    pub fn new(cell_size: usize) -> SvgRenderer {
+        SvgRenderer {
+            _cell_size: cell_size,
+            _shots: Vec::new(),
+        }
+    }
+
+    // A ball path in map cell coordinates, (x, y)
+    pub fn add_shot(&mut self, path: Vec<(f32, f32)>) {
+        self._shots.push(path);
+    }
+
+    pub fn cell_size(&self) -> usize {
+        self._cell_size
+    }
+
+    // The whole map as a standalone SVG document
+    pub fn export(&self, map: &Map) -> String {
+        let view = View::new(map.width() as isize, map.length() as isize);
+        let mut output = Vec::new();
+
+        self.display(&view, map, &[], &mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    // Each terrain gets its own class so it can be styled separately
+    fn terrain_class(terrain: Terrain) -> &'static str {
+        match terrain {
+            Terrain::Rough => "rough",
+            Terrain::Fairway => "fairway",
+            Terrain::Tee => "tee-ground",
+            Terrain::Hole => "hole-ground",
+            Terrain::WallVertical => "wall-vertical",
+            Terrain::WallHorizontal => "wall-horizontal",
+            Terrain::WallBackslash => "wall-backslash",
+            Terrain::WallSlash => "wall-slash",
+        }
+    }
+
+    // Wall line within a cell, as fractions of the cell from its top left corner
+    fn wall_segment(terrain: Terrain) -> Option<(f32, f32, f32, f32)> {
+        match terrain {
+            Terrain::WallVertical => Some((0.5, 0.0, 0.5, 1.0)),
+            Terrain::WallHorizontal => Some((0.0, 1.0, 1.0, 1.0)),
+            Terrain::WallBackslash => Some((0.0, 0.0, 1.0, 1.0)),
+            Terrain::WallSlash => Some((0.0, 1.0, 1.0, 0.0)),
+            _ => None,
+        }
+    }
+}
+
+impl ViewRenderer for SvgRenderer {
+    fn display<W: Write>(&self, view: &View, map: &Map, overlays: &[Overlay], target: &mut W) -> io::Result<()> {
+        let viewport = view.viewport(map);
+        let size = self._cell_size as f32;
+        let board = map.board();
+
+        writeln!(
+            target,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+            viewport.width() * self._cell_size,
+            viewport.height() * self._cell_size,
+            viewport.width() * self._cell_size,
+            viewport.height() * self._cell_size,
+        )?;
+        writeln!(target, "<title>{}</title>", escape(map.name()))?;
+        writeln!(target, "<style>{}</style>", STYLE)?;
+
+        let mut walls = Vec::new();
+        let mut markers = Vec::new();
+
+        for (y, row) in board.iter().enumerate().skip(viewport.y()).take(viewport.height()) {
+            for (x, cell) in row.iter().enumerate().skip(viewport.x()).take(viewport.width()) {
+                let left = ((x - viewport.x()) * self._cell_size) as f32;
+                let top = ((y - viewport.y()) * self._cell_size) as f32;
+                let class = SvgRenderer::terrain_class(cell.terrain());
+
+                writeln!(target, "<rect class=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>", class, left, top, size, size)?;
+
+                match cell.terrain() {
+                    Terrain::Hole => markers.push(("hole", left + size / 2.0, top + size / 2.0, size / 3.0)),
+                    Terrain::Tee => markers.push(("tee", left + size / 2.0, top + size / 2.0, size / 4.0)),
+                    terrain => {
+                        if let Some((x1, y1, x2, y2)) = SvgRenderer::wall_segment(terrain) {
+                            walls.push((left + x1 * size, top + y1 * size, left + x2 * size, top + y2 * size));
+                        }
+                    }
+                }
+            }
+        }
+
+        for (x1, y1, x2, y2) in walls {
+            writeln!(target, "<line class=\"wall\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>", x1, y1, x2, y2)?;
+        }
+
+        for (class, cx, cy, radius) in markers {
+            writeln!(target, "<circle class=\"{}\" cx=\"{}\" cy=\"{}\" r=\"{}\"/>", class, cx, cy, radius)?;
+        }
+
+        for shot in &self._shots {
+            let points: Vec<String> = shot
+                .iter()
+                .map(|&(x, y)| format!("{},{}", (x - viewport.x() as f32 + 0.5) * size, (y - viewport.y() as f32 + 0.5) * size))
+                .collect();
+
+            writeln!(target, "<polyline class=\"shot\" points=\"{}\"/>", points.join(" "))?;
+        }
+
+        for overlay in overlays {
+            let (x, y) = overlay.position();
+
+            if let Some((screen_x, screen_y)) = viewport.to_screen(x, y) {
+                let class = match overlay.kind() {
+                    OverlayKind::Ball => "ball",
+                    OverlayKind::Player(_) => "player",
+                };
+
+                writeln!(
+                    target,
+                    "<circle class=\"{}\" cx=\"{}\" cy=\"{}\" r=\"{}\"/>",
+                    class,
+                    (screen_x as f32 + 0.5) * size,
+                    (screen_y as f32 + 0.5) * size,
+                    size / 4.0,
+                )?;
+            }
+        }
+
+        writeln!(target, "</svg>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SvgRenderer;
+    use model::map::{MapBuilder, MapName, Terrain};
+    use view_model::view_base::{Overlay, View, ViewRenderer};
+
+    #[test]
+    fn exports_whole_map() {
+        let map = MapBuilder::from_named(None).unwrap();
+        let test = SvgRenderer::new(10).export(&map);
+
+        assert!(test.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"340\" height=\"120\""));
+        assert!(test.trim_end().ends_with("</svg>"));
+        assert_eq!(test.matches("<rect ").count(), 34 * 12);
+        assert_eq!(test.matches("class=\"hole\"").count(), 1);
+        assert_eq!(test.matches("class=\"tee\"").count(), 1);
+    }
+
+    #[test]
+    fn diagonal_walls_as_lines() {
+        let map = MapBuilder::from_named(Some(MapName::Spiral)).unwrap();
+        let test = SvgRenderer::new(10).export(&map);
+
+        // '/' at (9, 3) and '\' at (15, 3)
+        assert!(test.contains("<line class=\"wall\" x1=\"90\" y1=\"40\" x2=\"100\" y2=\"30\"/>"));
+        assert!(test.contains("<line class=\"wall\" x1=\"150\" y1=\"30\" x2=\"160\" y2=\"40\"/>"));
+    }
+
+    #[test]
+    fn shots_and_overlays() {
+        let map = MapBuilder::from_named(None).unwrap();
+        let mut test = SvgRenderer::new(10);
+        let mut output = Vec::new();
+
+        test.add_shot(vec![(9.0, 3.0), (20.0, 4.0)]);
+        test.display(&View::new(34, 12), &map, &[Overlay::ball(20, 4), Overlay::ball(40, 40)], &mut output).unwrap();
+
+        let svg = String::from_utf8(output).unwrap();
+
+        assert!(svg.contains("<polyline class=\"shot\" points=\"95,35 205,45\"/>"));
+        assert_eq!(svg.matches("class=\"ball\"").count(), 1);
+    }
+
+    #[test]
+    fn terrain_classes() {
+        let map = MapBuilder::from_named(None).unwrap();
+        let test = SvgRenderer::new(10).export(&map);
+
+        assert_eq!(test.matches("<rect class=\"tee-ground\"").count(), 1);
+        assert_eq!(test.matches("<rect class=\"hole-ground\"").count(), 1);
+        assert_eq!(test.matches("<rect class=\"fairway\"").count(), map.find(Terrain::Fairway).len());
+        assert_eq!(test.matches("<rect class=\"wall-vertical\"").count(), map.find(Terrain::WallVertical).len());
+        assert_eq!(test.matches("<rect class=\"wall-horizontal\"").count(), map.find(Terrain::WallHorizontal).len());
+        assert!(test.contains("<rect class=\"wall-horizontal\" x=\"70\" y=\"20\""));
+    }
+
+    #[test]
+    fn title_is_escaped() {
+        let map = MapBuilder::from_text("", "GolfMania map v2\nname: <script>alert(1)</script>\n---\nH*#H").unwrap();
+        let test = SvgRenderer::new(10).export(&map);
+
+        assert!(test.contains("<title>&lt;script&gt;alert(1)&lt;/script&gt;</title>"));
+        assert!(!test.contains("<script>"));
+    }
+}
@@ -0,0 +1,111 @@
+use std::io::{self, Write};
+
+use model::map::{Map, Terrain};
+use view_model::view_base::{overlay_at, Overlay, OverlayKind, View, ViewRenderer};
+
+const RESET: &str = "\x1b[0m";
+
+// Draws the board with ANSI colour escapes, one line per row of the view
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TerminalRenderer;
+
+impl TerminalRenderer {
+    // (background, foreground) SGR codes for a terrain
+    fn colours(terrain: Terrain) -> (u8, u8) {
+        match terrain {
+            Terrain::Rough => (42, 32),
+            Terrain::Fairway | Terrain::Tee => (102, 30),
+            Terrain::Hole => (40, 97),
+            _ => (107, 30),
+        }
+    }
+}
+
+impl ViewRenderer for TerminalRenderer {
+    fn display<W: Write>(&self, view: &View, map: &Map, overlays: &[Overlay], target: &mut W) -> io::Result<()> {
+        let viewport = view.viewport(map);
+        let board = map.board();
+
+        for (y, row) in board.iter().enumerate().skip(viewport.y()).take(viewport.height()) {
+            for (x, cell) in row.iter().enumerate().skip(viewport.x()).take(viewport.width()) {
+                let (background, foreground) = TerminalRenderer::colours(cell.terrain());
+
+                match overlay_at(overlays, x, y) {
+                    Some(overlay) => {
+                        let colour = match overlay.kind() {
+                            OverlayKind::Ball => 97,
+                            OverlayKind::Player(_) => 93,
+                        };
+                        write!(target, "\x1b[{};{}m{}", background, colour, overlay.glyph())?;
+                    }
+                    None => {
+                        let glyph = match cell.terrain() {
+                            Terrain::Rough | Terrain::Fairway => ' ',
+                            terrain => terrain.glyph(),
+                        };
+                        write!(target, "\x1b[{};{}m{}", background, foreground, glyph)?;
+                    }
+                }
+            }
+
+            writeln!(target, "{}", RESET)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TerminalRenderer;
+    use model::map::MapBuilder;
+    use view_model::view_base::{Overlay, View, ViewRenderer};
+
+    fn render(view: View, overlays: &[Overlay]) -> String {
+        let map = MapBuilder::from_named(None).unwrap();
+        let mut output = Vec::new();
+
+        TerminalRenderer.display(&view, &map, overlays, &mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn respects_view_dimensions() {
+        let test = render(View::new(10, 4), &[]);
+
+        assert_eq!(test.lines().count(), 4);
+        assert_eq!(test.lines().next().unwrap().matches("\x1b[42;").count(), 10);
+    }
+
+    #[test]
+    fn view_larger_than_map() {
+        assert_eq!(render(View::new(100, 100), &[]).lines().count(), 12);
+    }
+
+    #[test]
+    fn draws_only_the_camera_window() {
+        let map = MapBuilder::from_named(None).unwrap();
+        let mut view = View::new(4, 2);
+        let mut output = Vec::new();
+
+        view.pan(8, 3, &map);
+        TerminalRenderer.display(&view, &map, &[], &mut output).unwrap();
+
+        let test = String::from_utf8(output).unwrap();
+
+        assert_eq!(test.lines().count(), 2);
+        assert!(test.lines().next().unwrap().starts_with("\x1b[102;30m \x1b[102;30m*"));
+    }
+
+    #[test]
+    fn colours_terrain_and_ball() {
+        let test = render(View::new(34, 12), &[Overlay::ball(8, 4)]);
+        let tee_row = test.lines().nth(3).unwrap();
+
+        assert!(tee_row.contains("\x1b[102;30m*"));
+        assert!(tee_row.contains("\x1b[107;30m|"));
+        assert!(test.lines().nth(4).unwrap().contains("\x1b[102;97mo"));
+        assert!(test.contains("\x1b[40;97m#"));
+    }
+}
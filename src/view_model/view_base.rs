@@ -1,8 +1,129 @@
-pub struct View {
-    _width: isize,
-    _height: isize,
-}
-
-pub trait ViewRenderer {
-    fn display();
-}
\ No newline at end of file
+use std::io::{self, Write};
+
+use model::map::Map;
+use view_model::camera::{Camera, Viewport};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct View {
+    _width: isize,
+    _height: isize,
+    _camera: Camera,
+}
+
+impl View {
+    pub fn new(width: isize, height: isize) -> View {
+        View {
+            _width: width,
+            _height: height,
+            _camera: Camera::new(),
+        }
+    }
+
+    pub fn width(&self) -> isize {
+        self._width
+    }
+
+    pub fn height(&self) -> isize {
+        self._height
+    }
+
+    pub fn resize(&mut self, width: isize, height: isize) {
+        self._width = width;
+        self._height = height;
+    }
+
+    // Size in cells, negative dimensions showing nothing
+    pub fn size(&self) -> (usize, usize) {
+        (self._width.max(0) as usize, self._height.max(0) as usize)
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self._camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self._camera
+    }
+
+    pub fn pan(&mut self, dx: isize, dy: isize, map: &Map) {
+        let size = self.size();
+        self._camera.pan(dx, dy, size, map);
+    }
+
+    pub fn follow(&mut self, target: (usize, usize), map: &Map) {
+        let size = self.size();
+        self._camera.follow(target, size, map);
+    }
+
+    // The part of the map this view shows; renderers draw only this window
+    pub fn viewport(&self, map: &Map) -> Viewport {
+        self._camera.viewport(self.size(), map)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OverlayKind {
+    Ball,
+    Player(char),
+}
+
+// Something drawn on top of the board at a cell position, (x, y)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Overlay {
+    _kind: OverlayKind,
+    _position: (usize, usize),
+}
+
+impl Overlay {
+    pub fn ball(x: usize, y: usize) -> Overlay {
+        Overlay {
+            _kind: OverlayKind::Ball,
+            _position: (x, y),
+        }
+    }
+
+    pub fn player(glyph: char, x: usize, y: usize) -> Overlay {
+        Overlay {
+            _kind: OverlayKind::Player(glyph),
+            _position: (x, y),
+        }
+    }
+
+    pub fn kind(&self) -> OverlayKind {
+        self._kind
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        self._position
+    }
+
+    pub fn glyph(&self) -> char {
+        match self._kind {
+            OverlayKind::Ball => 'o',
+            OverlayKind::Player(glyph) => glyph,
+        }
+    }
+}
+
+// Topmost overlay at a cell, later overlays are drawn over earlier ones
+pub fn overlay_at(overlays: &[Overlay], x: usize, y: usize) -> Option<&Overlay> {
+    overlays.iter().rev().find(|overlay| overlay.position() == (x, y))
+}
+
+// Makes text safe to place inside HTML or SVG markup, including attribute values
+pub fn escape(text: &str) -> String {
+    text.chars()
+        .map(|ch| match ch {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&#39;".to_string(),
+            ch => ch.to_string(),
+        })
+        .collect()
+}
+
+pub trait ViewRenderer {
+    fn display<W: Write>(&self, view: &View, map: &Map, overlays: &[Overlay], target: &mut W) -> io::Result<()>;
+}
@@ -1,7 +1,302 @@
+use view_model::view_base::View;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Orientation {
+    // Children side by side, left to right
+    Horizontal,
+    // Children one above the other, top to bottom
+    Vertical,
+}
+
+// How much of a split a child takes along the split direction
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Constraint {
+    Fixed(usize),
+    Percent(usize),
+    // At least this much, sharing any space left over like Fill
+    Min(usize),
+    // An equal share of whatever the other children leave
+    Fill,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rect {
+    _x: usize,
+    _y: usize,
+    _width: usize,
+    _height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Rect {
+        Rect {
+            _x: x,
+            _y: y,
+            _width: width,
+            _height: height,
+        }
+    }
+
+    pub fn x(&self) -> usize {
+        self._x
+    }
+
+    pub fn y(&self) -> usize {
+        self._y
+    }
+
+    pub fn width(&self) -> usize {
+        self._width
+    }
+
+    pub fn height(&self) -> usize {
+        self._height
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ViewContainer {
+    Pane(String, View),
+    Split(Orientation, Vec<(Constraint, ViewContainer)>),
+    // Layered over the same area, drawn first to last
+    Stacked(Vec<ViewContainer>),
+    // Only the active tab is laid out
+    Tabbed(Vec<(String, ViewContainer)>, usize),
+}
+
+impl ViewContainer {
+    // Works out where every visible pane sits within the area, resizing its View to fit
+    pub fn layout(&mut self, area: Rect) -> Vec<(String, Rect)> {
+        let mut placed = Vec::new();
+        self.layout_into(area, &mut placed);
+        placed
+    }
+
+    fn layout_into(&mut self, area: Rect, placed: &mut Vec<(String, Rect)>) {
+        match *self {
+            ViewContainer::Pane(ref name, ref mut view) => {
+                view.resize(area.width() as isize, area.height() as isize);
+                placed.push((name.clone(), area));
+            }
+            ViewContainer::Split(orientation, ref mut children) => {
+                let length = match orientation {
+                    Orientation::Horizontal => area.width(),
+                    Orientation::Vertical => area.height(),
+                };
+                let constraints: Vec<Constraint> = children.iter().map(|&(constraint, _)| constraint).collect();
+                let mut offset = 0;
+
+                for ((_, child), size) in children.iter_mut().zip(split_lengths(&constraints, length)) {
+                    let child_area = match orientation {
+                        Orientation::Horizontal => Rect::new(area.x() + offset, area.y(), size, area.height()),
+                        Orientation::Vertical => Rect::new(area.x(), area.y() + offset, area.width(), size),
+                    };
+
+                    child.layout_into(child_area, placed);
+                    offset += size;
+                }
+            }
+            ViewContainer::Stacked(ref mut layers) => {
+                for layer in layers.iter_mut() {
+                    layer.layout_into(area, placed);
+                }
+            }
+            ViewContainer::Tabbed(ref mut tabs, active) => {
+                if let Some((_, tab)) = tabs.get_mut(active) {
+                    tab.layout_into(area, placed);
+                }
+            }
+        }
+    }
+
+    // Switches the first tabbed container holding the named tab over to it
+    pub fn select_tab(&mut self, tab_name: &str) -> bool {
+        match *self {
+            ViewContainer::Pane(..) => false,
+            ViewContainer::Split(_, ref mut children) => children.iter_mut().any(|(_, child)| child.select_tab(tab_name)),
+            ViewContainer::Stacked(ref mut layers) => layers.iter_mut().any(|layer| layer.select_tab(tab_name)),
+            ViewContainer::Tabbed(ref mut tabs, ref mut active) => {
+                match tabs.iter().position(|(name, _)| name == tab_name) {
+                    Some(idx) => {
+                        *active = idx;
+                        true
+                    }
+                    None => tabs.iter_mut().any(|(_, tab)| tab.select_tab(tab_name)),
+                }
+            }
+        }
+    }
+
+    pub fn view(&self, pane_name: &str) -> Option<&View> {
+        match *self {
+            ViewContainer::Pane(ref name, ref view) => if name == pane_name { Some(view) } else { None },
+            ViewContainer::Split(_, ref children) => children.iter().filter_map(|(_, child)| child.view(pane_name)).next(),
+            ViewContainer::Stacked(ref layers) => layers.iter().filter_map(|layer| layer.view(pane_name)).next(),
+            ViewContainer::Tabbed(ref tabs, _) => tabs.iter().filter_map(|(_, tab)| tab.view(pane_name)).next(),
+        }
+    }
+
+    pub fn view_mut(&mut self, pane_name: &str) -> Option<&mut View> {
+        match *self {
+            ViewContainer::Pane(ref name, ref mut view) => if name == pane_name { Some(view) } else { None },
+            ViewContainer::Split(_, ref mut children) => children.iter_mut().filter_map(|(_, child)| child.view_mut(pane_name)).next(),
+            ViewContainer::Stacked(ref mut layers) => layers.iter_mut().filter_map(|layer| layer.view_mut(pane_name)).next(),
+            ViewContainer::Tabbed(ref mut tabs, _) => tabs.iter_mut().filter_map(|(_, tab)| tab.view_mut(pane_name)).next(),
+        }
+    }
+}
+
+// Sizes along a split. Fixed, percentage and minimum sizes are handed out
+// first, then Min and Fill children share what's left. If the constraints ask
+// for more than there is, the later children are cut short.
+fn split_lengths(constraints: &[Constraint], length: usize) -> Vec<usize> {
+    let requested: Vec<usize> = constraints
+        .iter()
+        .map(|constraint| match *constraint {
+            Constraint::Fixed(size) | Constraint::Min(size) => size,
+            // Split up so that even the longest length can't overflow
+            Constraint::Percent(percent) => {
+                let percent = percent.min(100);
+                length / 100 * percent + length % 100 * percent / 100
+            }
+            Constraint::Fill => 0,
+        })
+        .collect();
+
+    let flexible = constraints
+        .iter()
+        .filter(|constraint| matches!(**constraint, Constraint::Min(_) | Constraint::Fill))
+        .count();
+    let leftover = length.saturating_sub(requested.iter().fold(0, |total, &size| total.saturating_add(size)));
+
+    let mut remaining = length;
+    let mut shared = 0;
+
+    constraints
+        .iter()
+        .zip(requested)
+        .map(|(constraint, size)| {
+            let size = match *constraint {
+                Constraint::Min(_) | Constraint::Fill => {
+                    // The first flexible children pick up any remainder
+                    let extra = leftover / flexible + (shared < leftover % flexible) as usize;
+                    shared += 1;
+                    size + extra
+                }
+                _ => size,
+            };
+
+            let size = size.min(remaining);
+            remaining -= size;
+            size
+        })
+        .collect()
+}
+
 pub struct ViewContainerFactory {
 
 }
 
 impl ViewContainerFactory {
-    
-}
\ No newline at end of file
+    pub fn pane(name: &str) -> ViewContainer {
+        ViewContainer::Pane(name.to_string(), View::new(0, 0))
+    }
+
+    pub fn split(orientation: Orientation, children: Vec<(Constraint, ViewContainer)>) -> ViewContainer {
+        ViewContainer::Split(orientation, children)
+    }
+
+    pub fn stacked(layers: Vec<ViewContainer>) -> ViewContainer {
+        ViewContainer::Stacked(layers)
+    }
+
+    pub fn tabbed(tabs: Vec<(&str, ViewContainer)>) -> ViewContainer {
+        ViewContainer::Tabbed(
+            tabs.into_iter().map(|(name, tab)| (name.to_string(), tab)).collect(),
+            0,
+        )
+    }
+
+    // The standard play screen: the course on the left, the scorecard panel
+    // on the right, and the shot power meter along the bottom
+    pub fn course_screen() -> ViewContainer {
+        ViewContainerFactory::split(
+            Orientation::Vertical,
+            vec![
+                (
+                    Constraint::Fill,
+                    ViewContainerFactory::split(
+                        Orientation::Horizontal,
+                        vec![
+                            (Constraint::Min(20), ViewContainerFactory::pane("course")),
+                            (Constraint::Fixed(24), ViewContainerFactory::pane("scorecard")),
+                        ]
+                    )
+                ),
+                (Constraint::Fixed(3), ViewContainerFactory::pane("power")),
+            ]
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_lengths, Constraint, Orientation, Rect, ViewContainerFactory};
+
+    #[test]
+    fn split_constraints() {
+        use self::Constraint::*;
+
+        assert_eq!(split_lengths(&[Fixed(10), Fill, Fill], 31), vec![10, 11, 10]);
+        assert_eq!(split_lengths(&[Percent(25), Min(5), Fill], 40), vec![10, 18, 12]);
+        assert_eq!(split_lengths(&[Fixed(30), Fixed(30)], 40), vec![30, 10]);
+        assert_eq!(split_lengths(&[Fixed(usize::MAX), Min(usize::MAX), Fill], 40), vec![40, 0, 0]);
+        assert_eq!(split_lengths(&[Percent(50), Fill], usize::MAX), vec![usize::MAX / 2, usize::MAX - usize::MAX / 2]);
+    }
+
+    #[test]
+    fn course_screen_layout() {
+        let mut test = ViewContainerFactory::course_screen();
+        let placed = test.layout(Rect::new(0, 0, 80, 24));
+
+        assert_eq!(placed[0], ("course".to_string(), Rect::new(0, 0, 56, 21)));
+        assert_eq!(placed[1], ("scorecard".to_string(), Rect::new(56, 0, 24, 21)));
+        assert_eq!(placed[2], ("power".to_string(), Rect::new(0, 21, 80, 3)));
+        assert_eq!(test.view("course").unwrap().width(), 56);
+        assert!(test.view("missing").is_none());
+    }
+
+    #[test]
+    fn stacked_share_area() {
+        let mut test = ViewContainerFactory::stacked(vec![
+            ViewContainerFactory::pane("course"),
+            ViewContainerFactory::pane("hud"),
+        ]);
+
+        let placed = test.layout(Rect::new(2, 2, 10, 10));
+
+        assert_eq!(placed[0].1, placed[1].1);
+    }
+
+    #[test]
+    fn tabs_lay_out_active_only() {
+        let mut test = ViewContainerFactory::split(
+            Orientation::Horizontal,
+            vec![
+                (Constraint::Fill, ViewContainerFactory::tabbed(vec![
+                    ("Course", ViewContainerFactory::pane("course")),
+                    ("Scores", ViewContainerFactory::pane("scorecard")),
+                ])),
+            ]
+        );
+
+        assert_eq!(test.layout(Rect::new(0, 0, 10, 10))[0].0, "course");
+        assert!(test.select_tab("Scores"));
+        assert!(!test.select_tab("Missing"));
+        assert_eq!(test.layout(Rect::new(0, 0, 10, 10))[0].0, "scorecard");
+
+        test.view_mut("course").unwrap().resize(1, 1);
+
+        assert_eq!(test.view("course").unwrap().height(), 1);
+    }
+}